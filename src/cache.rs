@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::ArgMatches;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::context::Context;
+use crate::utils::data_dir;
+
+/// Writes within this process go through the cache one at a time
+static CACHE: Mutex<()> = Mutex::new(());
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// `[cache]`, API responses remembered between runs
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub directory: PathBuf,
+    /// Seconds each kind of entry is kept, 0 doesn't cache it at all
+    pub channel_ids: u64,
+    pub twitch_users: u64,
+    pub videos: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            directory: data_dir().join("cache"),
+            channel_ids: 30 * DAY,
+            twitch_users: 30 * DAY,
+            videos: 7 * DAY,
+        }
+    }
+}
+
+/// What's cached, each kind in its own file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// YouTube channel ids keyed by handle
+    ChannelIds,
+    /// Twitch user ids keyed by login
+    TwitchUsers,
+    /// Details of finished YouTube videos keyed by video id, live and
+    /// upcoming ones change too often
+    Videos,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 3] = [Kind::ChannelIds, Kind::TwitchUsers, Kind::Videos];
+    pub const NAMES: [&str; 3] = ["channel-ids", "twitch-users", "videos"];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::ChannelIds => Self::NAMES[0],
+            Kind::TwitchUsers => Self::NAMES[1],
+            Kind::Videos => Self::NAMES[2],
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    fn ttl(self, config: &CacheConfig) -> u64 {
+        match self {
+            Kind::ChannelIds => config.channel_ids,
+            Kind::TwitchUsers => config.twitch_users,
+            Kind::Videos => config.videos,
+        }
+    }
+
+    fn file(self, config: &CacheConfig) -> PathBuf {
+        config.directory.join(format!("{}.json", self.name()))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Entry {
+    stored: DateTime<Utc>,
+    value: Value,
+}
+
+impl Entry {
+    fn is_fresh(&self, ttl: u64, now: DateTime<Utc>) -> bool {
+        now - self.stored < TimeDelta::seconds(ttl as i64)
+    }
+}
+
+/// A missing or unreadable file is an empty cache
+fn load(file: &Path) -> BTreeMap<String, Entry> {
+    fs::read_to_string(file)
+        .ok()
+        .and_then(|entries| serde_json::from_str(&entries).ok())
+        .unwrap_or_default()
+}
+
+/// Written next to the file and renamed over it, so readers never see half
+/// of it
+fn save(file: &Path, entries: &BTreeMap<String, Entry>) -> io::Result<()> {
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = file.with_extension(format!("json.{}.tmp", std::process::id()));
+    fs::write(&temp, serde_json::to_string(entries)?)?;
+    fs::rename(&temp, file).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+pub fn get<T: DeserializeOwned>(config: &CacheConfig, kind: Kind, key: &str) -> Option<T> {
+    get_many(config, kind, &[key.to_string()]).remove(key)
+}
+
+/// The entries of `keys` that are cached and haven't expired
+pub fn get_many<T: DeserializeOwned>(
+    config: &CacheConfig,
+    kind: Kind,
+    keys: &[String],
+) -> HashMap<String, T> {
+    let ttl = kind.ttl(config);
+    if ttl == 0 || keys.is_empty() {
+        return HashMap::new();
+    }
+    let now = Utc::now();
+    let mut entries = load(&kind.file(config));
+    keys.iter()
+        .filter_map(|key| {
+            let entry = entries
+                .remove(key)
+                .filter(|entry| entry.is_fresh(ttl, now))?;
+            let value = serde_json::from_value(entry.value).ok()?;
+            Some((key.clone(), value))
+        })
+        .collect()
+}
+
+/// Remember `entries`, dropping the ones that have expired in the meantime
+pub fn insert<T: Serialize>(
+    config: &CacheConfig,
+    kind: Kind,
+    entries: impl IntoIterator<Item = (String, T)>,
+) {
+    let ttl = kind.ttl(config);
+    if ttl == 0 {
+        return;
+    }
+    let now = Utc::now();
+    let mut new_entries = entries
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, serde_json::to_value(value).ok()?)))
+        .peekable();
+    if new_entries.peek().is_none() {
+        return;
+    }
+
+    let _guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let file = kind.file(config);
+    let mut cached = load(&file);
+    cached.retain(|_, entry| entry.is_fresh(ttl, now));
+    cached.extend(new_entries.map(|(key, value)| (key, Entry { stored: now, value })));
+    if let Err(e) = save(&file, &cached) {
+        eprintln!("Failed to save the {} cache: {e}", kind.name());
+    }
+}
+
+/// Forget everything of `kind`, or of every kind when `None`
+pub fn clear(config: &CacheConfig, kind: Option<Kind>) -> io::Result<usize> {
+    let _guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cleared = 0;
+    for kind in kind.map_or(Kind::ALL.to_vec(), |kind| vec![kind]) {
+        let file = kind.file(config);
+        cleared += load(&file).len();
+        match fs::remove_file(&file) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+    }
+    Ok(cleared)
+}
+
+/// `streamdex cache show|clear [kind]`
+pub fn run(ctx: &Context, args: &ArgMatches) -> color_eyre::Result<()> {
+    let config = &ctx.config.cache;
+    let (command, args) = args.subcommand().expect("Required by clap");
+    let kind = args
+        .get_one::<String>("kind")
+        .and_then(|kind| Kind::from_name(kind));
+    match command {
+        "clear" => {
+            let cleared = clear(config, kind)?;
+            println!("Cleared {cleared} cached entries");
+        }
+        _ => show(config, kind),
+    }
+    Ok(())
+}
+
+fn show(config: &CacheConfig, kind: Option<Kind>) {
+    let now = Utc::now();
+    for kind in kind.map_or(Kind::ALL.to_vec(), |kind| vec![kind]) {
+        let ttl = kind.ttl(config);
+        let entries = load(&kind.file(config));
+        if ttl == 0 {
+            println!("{}: not cached", kind.name());
+            continue;
+        }
+        println!(
+            "{}: {} entries, kept for {}",
+            kind.name(),
+            entries.len(),
+            format_age(TimeDelta::seconds(ttl as i64))
+        );
+        for (key, entry) in &entries {
+            let value = match &entry.value {
+                Value::String(value) => format!(" {value}"),
+                _ => String::new(),
+            };
+            let expired = if entry.is_fresh(ttl, now) {
+                ""
+            } else {
+                ", expired"
+            };
+            println!(
+                "  {key:<24}{value} ({} ago{expired})",
+                format_age(now - entry.stored)
+            );
+        }
+    }
+}
+
+/// `3d 4h`, `4h 5m`, `5m` or `30s`
+fn format_age(age: TimeDelta) -> String {
+    // Entries written by a machine with its clock ahead
+    let age = age.max(TimeDelta::zero());
+    match (age.num_days(), age.num_hours() % 24, age.num_minutes() % 60) {
+        (0, 0, 0) => format!("{}s", age.num_seconds()),
+        (0, 0, minutes) => format!("{minutes}m"),
+        (0, hours, minutes) => format!("{hours}h {minutes}m"),
+        (days, hours, _) => format!("{days}d {hours}h"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let config = CacheConfig {
+            directory: std::env::temp_dir()
+                .join(format!("streamdex-test-cache-{}", std::process::id())),
+            videos: 0,
+            ..Default::default()
+        };
+        assert_eq!(get::<String>(&config, Kind::ChannelIds, "handle"), None);
+
+        insert(
+            &config,
+            Kind::ChannelIds,
+            [
+                (String::from("handle"), "UCabc"),
+                (String::from("other"), "UCdef"),
+            ],
+        );
+        assert_eq!(
+            get::<String>(&config, Kind::ChannelIds, "handle").as_deref(),
+            Some("UCabc")
+        );
+        let keys = [String::from("other"), String::from("missing")];
+        let found = get_many::<String>(&config, Kind::ChannelIds, &keys);
+        assert_eq!(found.len(), 1);
+
+        // Expired entries are ignored, then dropped on the next write
+        let file = Kind::ChannelIds.file(&config);
+        let mut entries = load(&file);
+        entries.get_mut("other").unwrap().stored -= TimeDelta::days(31);
+        save(&file, &entries).unwrap();
+        assert_eq!(get::<String>(&config, Kind::ChannelIds, "other"), None);
+        insert(&config, Kind::ChannelIds, [(String::from("new"), "UCghi")]);
+        assert_eq!(load(&file).len(), 2);
+
+        // Disabled kinds are never written
+        insert(&config, Kind::Videos, [(String::from("video"), "details")]);
+        assert!(!Kind::Videos.file(&config).exists());
+
+        assert_eq!(clear(&config, None).unwrap(), 2);
+        assert_eq!(get::<String>(&config, Kind::ChannelIds, "handle"), None);
+        let _ = fs::remove_dir_all(&config.directory);
+    }
+}
@@ -1,31 +1,32 @@
 use clap::{Arg, ArgAction, ArgMatches, Command};
 
+use crate::cache::Kind;
+use crate::remote::RemoteCommand;
+
 pub fn parse() -> ArgMatches {
     Command::new("streamdex")
         .args(&[
             Arg::new("target")
                 .index(1)
+                .num_args(1..)
+                .value_name("TARGET")
                 .required(false)
                 .required_unless_present("tui")
-                .help("URL or alias of the live stream or video"),
+                .help(
+                    "URL or alias of the live stream or video, followed by the interval in \
+                    minutes to check for updates and how many minutes minimal to watch the \
+                    upcoming stream. With --multiview every value is a target",
+                ),
             Arg::new("format")
                 .short('f')
                 .long("format")
-                .help("Format of the target video"),
+                .help("Format of the target video, or a preset: best, 720p, 480p-lowfps, audio"),
             Arg::new("wait-for-live")
                 .short('w')
                 .long("wait-for-live")
                 .action(ArgAction::SetTrue)
                 .required(false)
                 .help("Wait for the live stream to go live if it's not currently live"),
-            Arg::new("interval")
-                .index(2)
-                .required(false)
-                .help("Interval in seconds to check for new updates"),
-            Arg::new("threshold")
-                .index(3)
-                .required(false)
-                .help("How many minutes minimal to watch the upcoming stream"),
             Arg::new("youtube")
                 .long("youtube")
                 .action(ArgAction::SetTrue)
@@ -39,8 +40,103 @@ pub fn parse() -> ArgMatches {
                 .long("print-format")
                 .action(ArgAction::SetTrue)
                 .help("Print yt-dlp and vlc command to stdout"),
+            Arg::new("dry-run")
+                .long("dry-run")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("text")
+                .value_parser(["text", "json"])
+                .help(
+                    "Resolve the target and print the launch plan instead of watching it, \
+                    --dry-run=json prints it as JSON",
+                ),
+            Arg::new("record")
+                .short('r')
+                .long("record")
+                .action(ArgAction::SetTrue)
+                .help("Record the stream to disk while watching"),
+            Arg::new("record-only")
+                .long("record-only")
+                .action(ArgAction::SetTrue)
+                .help("Wait for the stream and record it without opening a player"),
+            Arg::new("audio-only")
+                .short('a')
+                .long("audio-only")
+                .action(ArgAction::SetTrue)
+                .help("Listen without a video window, picking an audio format"),
+            Arg::new("from")
+                .long("from")
+                .help("Start at this timestamp, e.g. 1:23:45"),
+            Arg::new("to")
+                .long("to")
+                .help("Stop at this timestamp, e.g. 1:40:00"),
+            Arg::new("live-from-start")
+                .long("live-from-start")
+                .action(ArgAction::SetTrue)
+                .help("Join a live stream from its beginning"),
+            Arg::new("multiview")
+                .long("multiview")
+                .action(ArgAction::SetTrue)
+                .help("Watch all targets at once, tiling the player windows in a grid"),
             Arg::new("tui").action(ArgAction::SetTrue).long("tui"),
         ])
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("remote")
+                .about("Control a running player, lists the running players without arguments")
+                .args(&[
+                    Arg::new("session")
+                        .index(1)
+                        .help("Player to control, e.g. sora-youtube or just sora"),
+                    Arg::new("command")
+                        .index(2)
+                        .value_parser(RemoteCommand::NAMES)
+                        .help("Player command"),
+                    Arg::new("value")
+                        .index(3)
+                        .allow_hyphen_values(true)
+                        .help("Volume (50, +5, -5) or seek position (1:23:45, +30, -10)"),
+                ]),
+        )
+        .subcommand(
+            Command::new("formats")
+                .about("List the formats offered by a live stream or a video")
+                .args(&[
+                    Arg::new("target")
+                        .index(1)
+                        .required(true)
+                        .help("URL, @handle or alias, channels are resolved to their live stream"),
+                    Arg::new("youtube")
+                        .long("youtube")
+                        .action(ArgAction::SetTrue)
+                        .help("Only list the youtube stream of an alias"),
+                    Arg::new("twitch")
+                        .long("twitch")
+                        .action(ArgAction::SetTrue)
+                        .help("Only list the twitch stream of an alias"),
+                ]),
+        )
+        .subcommand(Command::new("status").about("Show the YouTube API quota used today"))
+        .subcommand(
+            Command::new("cache")
+                .about("Show or clear what's cached from the YouTube and Twitch APIs")
+                .subcommand_required(true)
+                .subcommands(
+                    [
+                        ("show", "List the cached entries"),
+                        ("clear", "Forget the cached entries"),
+                    ]
+                    .map(|(name, about)| {
+                        Command::new(name).about(about).arg(
+                            Arg::new("kind")
+                                .index(1)
+                                .value_parser(Kind::NAMES)
+                                .help("Only this kind of entries, all of them without it"),
+                        )
+                    }),
+                ),
+        )
         .get_matches()
     // .subcommand_required(true)
     // .subcommand(
@@ -173,10 +269,5 @@ pub fn parse() -> ArgMatches {
     // //             .help("delay in seconds")
     // //     ])
     // // )
-    // .subcommand(
-    //     Command::new("print-formats")
-    //         .about("Print available formats for a live stream or a video")
-    //         .args(&[Arg::new("id").index(1).required(true).help("room id")]),
-    // )
     // .get_matches()
 }
@@ -1,7 +1,18 @@
-use std::{env, fs, io, path::PathBuf};
+use std::{collections::HashMap, env, fs, io, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::cache::CacheConfig;
+use crate::extractor::{Extractor, ExtractorBackend, YtDlpOptions};
+use crate::feed::DiscoveryConfig;
+use crate::hooks::HooksConfig;
+use crate::multiview::MultiviewConfig;
+use crate::player::{Player, PlayerBackend};
+use crate::preset::{self, Preset};
+use crate::quota::QuotaConfig;
+use crate::resume::ResumeConfig;
+use crate::target::Platform;
+
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct Config {
     pub tui_targets: PathBuf,
@@ -9,12 +20,41 @@ pub struct Config {
     pub new_aliases: PathBuf,
     pub client_secret: PathBuf,
     pub presist_token: PathBuf,
-    pub saved_yt_channel_ids: PathBuf,
+    /// Channel ids saved by older versions, read when `[cache]` doesn't have them
+    #[serde(default)]
+    pub saved_yt_channel_ids: Option<PathBuf>,
+    /// Used in turn, the next one takes over when a key runs out of quota
+    #[serde(default)]
+    pub youtube_api_keys: Vec<String>,
     pub twitch_client_id: String,
     pub twitch_client_secret: PathBuf,
     pub twitch_access_token: PathBuf,
     pub default_parameters: DefaultParameters,
-
+    #[serde(default)]
+    pub player: PerPlatform<PlayerBackend>,
+    #[serde(default)]
+    pub extractor: PerPlatform<ExtractorBackend>,
+    #[serde(default)]
+    pub ytdlp: YtDlpConfig,
+    #[serde(default)]
+    pub record: RecordConfig,
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+    #[serde(default)]
+    pub multiview: MultiviewConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub resume: ResumeConfig,
+    #[serde(default)]
+    pub quota: QuotaConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// Quality presets in addition to (or replacing) the built-in ones
+    #[serde(default)]
+    pub presets: HashMap<String, Preset>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -23,18 +63,111 @@ pub struct DefaultParameters {
     pub wait_for_live: bool,
     pub interval: u64,
     pub threshold: i64,
+    /// Minutes to wait for a stream to go live before giving up, 0 waits forever
+    #[serde(default)]
+    pub wait_timeout: u64,
 }
 
+/// Raw formats or preset names
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct Formats {
     pub youtube: String,
     pub twitch: String,
 }
 
+/// A setting with a global default and optional per-platform overrides
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct PerPlatform<T> {
+    #[serde(default)]
+    pub default: T,
+    pub youtube: Option<T>,
+    pub twitch: Option<T>,
+}
+
+impl<T> PerPlatform<T> {
+    pub fn get(&self, platform: &Platform) -> &T {
+        let value = match platform {
+            Platform::YouTube => self.youtube.as_ref(),
+            Platform::Twitch => self.twitch.as_ref(),
+            Platform::Unknown => None,
+        };
+        value.unwrap_or(&self.default)
+    }
+}
+
+/// `[ytdlp]` with `[ytdlp.youtube]` and `[ytdlp.twitch]` overriding single fields
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct YtDlpConfig {
+    #[serde(flatten)]
+    pub default: YtDlpOptions,
+    pub youtube: Option<YtDlpOptions>,
+    pub twitch: Option<YtDlpOptions>,
+}
+
+impl YtDlpConfig {
+    pub fn get(&self, platform: &Platform) -> YtDlpOptions {
+        let platform_options = match platform {
+            Platform::YouTube => self.youtube.as_ref(),
+            Platform::Twitch => self.twitch.as_ref(),
+            Platform::Unknown => None,
+        };
+        match platform_options {
+            Some(options) => self.default.merge(options),
+            None => self.default.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RecordConfig {
+    /// Record every stream without passing `--record`
+    pub enabled: bool,
+    pub directory: PathBuf,
+    /// Placeholders: `{alias}`, `{platform}`, `{id}`, `{title}`, `{start}` or `{start:<strftime>}`
+    pub template: String,
+}
+
+impl Default for RecordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: PathBuf::from("."),
+            template: String::from("{alias}/{start}_{title}_{id}.ts"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ReconnectConfig {
+    /// How many times to relaunch the extractor after the stream drops
+    pub retries: u32,
+    /// Seconds to wait before the first retry, doubled on every attempt
+    pub backoff: u64,
+    pub max_backoff: u64,
+    /// Seconds without data before a live stream's extractor is restarted, 0
+    /// disables it
+    pub stall_timeout: u64,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            retries: 5,
+            backoff: 5,
+            max_backoff: 60,
+            stall_timeout: 30,
+        }
+    }
+}
+
 impl Config {
     pub fn get() -> Self {
-        let config_path = env::var("STREAMDEX_CONFIG").unwrap_or({
-            let home = std::env::var("HOME").unwrap_or(std::env::var("USERPROFILE").unwrap());
+        let config_path = env::var("STREAMDEX_CONFIG").unwrap_or_else(|_| {
+            let home = std::env::var("HOME")
+                .or_else(|_| std::env::var("USERPROFILE"))
+                .unwrap();
             format!("{home}/.config/streamdex/config.toml")
         });
         let config_str = fs::read_to_string(&config_path)
@@ -48,6 +181,19 @@ impl Config {
             .unwrap();
         toml::from_str::<Config>(&config_str).unwrap()
     }
+
+    pub fn player(&self, platform: &Platform) -> Box<dyn Player> {
+        self.player.get(platform).build()
+    }
+
+    /// Format to hand to the extractor, with presets resolved
+    pub fn format(&self, format: &str, platform: &Platform) -> String {
+        preset::resolve(&self.presets, format, platform)
+    }
+
+    pub fn extractor(&self, platform: &Platform) -> Box<dyn Extractor> {
+        self.extractor.get(platform).build(self.ytdlp.get(platform))
+    }
 }
 
 // #[cfg(test)]
@@ -1,50 +1,140 @@
 use clap::ArgMatches;
+use color_eyre::eyre::eyre;
 
 use crate::{
     config::Config,
+    extractor::TimeRange,
+    multiview::Geometry,
+    plan::DryRun,
+    supervisor::Supervisor,
     target::{CliTarget, ToCliTarget},
 };
 
+#[derive(Clone)]
 pub struct Context {
     pub config: Config,
+    /// Target being handled, the first one unless watching several
     pub target: Option<CliTarget>,
+    pub targets: Vec<CliTarget>,
+    /// Tile the players of all targets over the screen
+    pub multiview: bool,
+    /// Player window placement, set per target with `--multiview`
+    pub geometry: Option<Geometry>,
     pub format: Option<String>,
     pub interval: Option<u64>,
     pub wait_for_live: bool,
     pub threshold: Option<i64>,
-    // pub from: Option<String>,
-    // pub to: Option<String>,
+    pub range: Option<TimeRange>,
+    pub live_from_start: bool,
     pub print_command: bool,
+    pub dry_run: Option<DryRun>,
+    pub record: bool,
+    pub record_only: bool,
+    /// Play without video, with the `audio` preset unless a format is given
+    pub audio_only: bool,
+    /// Playing a video rather than a broadcast, where it's stopped is remembered
+    pub vod: bool,
+    pub supervisor: Supervisor,
 }
 
 impl Context {
     pub fn new(config: Config, cli: &ArgMatches) -> color_eyre::Result<Self> {
-        let target = cli
-            .get_one::<String>("target")
+        let multiview = cli.get_flag("multiview");
+        let mut positionals = cli
+            .get_many::<String>("target")
+            .into_iter()
+            .flatten()
             .cloned()
-            .map(|mut string| string.to_target());
-        let format = cli.get_one::<String>("format").cloned();
-        let interval = cli
-            .get_one::<String>("interval")
-            .cloned()
-            .map(|s| s.parse::<u64>())
+            .collect::<Vec<_>>();
+        // Without --multiview the target is followed by the interval and threshold
+        let (interval, threshold) = match multiview {
+            true => (None, None),
+            false => {
+                let mut rest = positionals.drain(positionals.len().min(1)..);
+                let interval = rest.next();
+                let threshold = rest.next();
+                if let Some(extra) = rest.next() {
+                    return Err(eyre!(
+                        "Unexpected argument {extra}, use --multiview to watch several targets"
+                    ));
+                }
+                (interval, threshold)
+            }
+        };
+        let interval = interval
+            .map(|interval| {
+                interval.parse::<u64>().map_err(|_| {
+                    eyre!("Invalid interval {interval}, use --multiview to watch several targets")
+                })
+            })
             .transpose()?;
-        let threshold = cli.get_one("threshold").cloned();
-        let wait_for_live = if cli.get_flag("wait-for-live") {
+        let threshold = threshold
+            .map(|threshold| {
+                threshold
+                    .parse::<i64>()
+                    .map_err(|_| eyre!("Invalid threshold {threshold}"))
+            })
+            .transpose()?;
+        let targets = positionals
+            .into_iter()
+            .map(|mut string| string.to_target())
+            .collect::<Vec<_>>();
+        let target = targets.first().cloned();
+        let format = cli.get_one::<String>("format").cloned();
+        let range = TimeRange::new(
+            cli.get_one::<String>("from").map(String::as_str),
+            cli.get_one::<String>("to").map(String::as_str),
+        )?;
+        let live_from_start = cli.get_flag("live-from-start");
+        let record_only = cli.get_flag("record-only");
+        let audio_only = cli.get_flag("audio-only");
+        let dry_run = cli
+            .get_one::<String>("dry-run")
+            .and_then(|format| DryRun::from_arg(format));
+        // A dry run shows what would be launched right now, it never waits
+        let wait_for_live = if dry_run.is_some() {
+            false
+        } else if cli.get_flag("wait-for-live") || record_only {
             true
         } else {
             config.default_parameters.wait_for_live
         };
         let print_command = cli.get_flag("print-command");
+        let record = cli.get_flag("record") || config.record.enabled;
 
         Ok(Self {
             config,
             target,
+            targets,
+            multiview,
+            geometry: None,
             format,
             interval,
             wait_for_live,
             threshold,
+            range,
+            live_from_start,
             print_command,
+            dry_run,
+            record,
+            record_only,
+            audio_only,
+            vod: false,
+            supervisor: Supervisor::default(),
         })
     }
+
+    /// Record without a player, waiting for the target to go live first
+    pub fn set_record_only(&mut self) {
+        self.record_only = true;
+        self.wait_for_live = self.dry_run.is_none();
+    }
+
+    /// Alias name given on the command line, if the target is an alias
+    pub fn alias(&self) -> Option<&str> {
+        match &self.target {
+            Some(CliTarget::MaubeAlias(alias)) => Some(alias),
+            _ => None,
+        }
+    }
 }
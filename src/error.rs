@@ -13,17 +13,58 @@ pub enum Error {
     #[error("No channel found with handle/username: {0}")]
     NoChannelFound(String),
     #[error("Fetching youtube live stream failed from channel_id {0}")]
-    YTFetchLiveFailed(String, google_youtube3::Error),
+    YTFetchLiveFailed(String, Box<google_youtube3::Error>),
+    #[error("Fetching the youtube feed failed for channel_id {0}")]
+    YTFetchFeedFailed(String, reqwest::Error),
+    #[error("Fetching the youtube channel page {0} failed")]
+    YTFetchChannelPageFailed(String, reqwest::Error),
     #[error("Fetching youtube video detail failed")]
-    YTFailFetchVideoDetail(google_youtube3::Error),
+    YTFailFetchVideoDetail(Box<google_youtube3::Error>),
     #[error("Data {0} not found in fetch result")]
     NoDataFound(FetchData),
     #[error("Failed to extract username from {0}({1}) url")]
     FailExtractUsername(Platform, Url),
+    #[error("{0} is for channel members only")]
+    MembersOnly(String),
+    #[error("{0} is age-restricted")]
+    AgeRestricted(String),
+    #[error("{0} is not available in your country")]
+    GeoBlocked(String),
+    #[error("Format {1} is not available for {0}")]
+    FormatUnavailable(String, String),
+    #[error("Cookies for {0} are missing or expired")]
+    CookiesExpired(String),
+    #[error("{0} is offline")]
+    StreamOffline(String),
     #[error("Anyhow Error {0}")]
     Anyhow(anyhow::Error),
     #[error("Eyre Error {0}")]
-    Eyre(color_eyre::eyre::Error)
+    Eyre(color_eyre::eyre::Error),
+}
+
+impl Error {
+    /// What the user can do about an extractor failure
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Error::MembersOnly(_) => {
+                Some("Use cookies of an account with a membership, see `cookies` under [ytdlp]")
+            }
+            Error::AgeRestricted(_) => {
+                Some("Use cookies of a signed in account, see `cookies` under [ytdlp]")
+            }
+            Error::GeoBlocked(_) => {
+                Some("Go through a proxy, e.g. `extra_args = [\"--proxy\", \"...\"]` under [ytdlp]")
+            }
+            Error::FormatUnavailable(_, _) => {
+                Some("Pick another format with -f, `streamdex formats` lists them")
+            }
+            Error::CookiesExpired(_) => {
+                Some("Sign in again in the browser, or export a fresh cookies.txt")
+            }
+            Error::StreamOffline(_) => Some("Use -w to wait until it goes live"),
+            _ => None,
+        }
+    }
 }
 
 impl From<anyhow::Error> for Error {
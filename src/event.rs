@@ -5,6 +5,9 @@ use crossterm::event::KeyModifiers;
 use futures::{FutureExt, StreamExt};
 use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
+use crate::remote::RemoteCommand;
+use crate::tui::FormatChoice;
+
 pub enum Event {
     Tick,
     Crossterm(crossterm::event::Event),
@@ -15,6 +18,15 @@ pub enum TuiEvent {
     Move(Direction),
     MovePanel(Direction),
     DoAction(KeyModifiers),
+    Launch,
+    EditRange,
+    CyclePreset,
+    ToggleAudioOnly,
+    PickFormat,
+    Formats(Vec<FormatChoice>),
+    Remote(RemoteCommand),
+    StopSession,
+    Notify(String),
     Exit,
 }
 
@@ -59,6 +71,11 @@ impl EventHandler {
     pub fn send(&self, event: TuiEvent) {
         let _ = self.sender.send(Event::Tui(event));
     }
+
+    /// Sender for tasks that report back to the TUI
+    pub fn sender(&self) -> UnboundedSender<Event> {
+        self.sender.clone()
+    }
 }
 
 pub struct EventTask {
@@ -0,0 +1,699 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::utils::{format_timestamp, parse_timestamp};
+
+/// Extractor selection as written in the config file
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub enum ExtractorBackend {
+    #[default]
+    #[serde(rename = "yt-dlp")]
+    YtDlp,
+    #[serde(rename = "streamlink")]
+    Streamlink,
+}
+
+impl ExtractorBackend {
+    pub fn build(&self, ytdlp_options: YtDlpOptions) -> Box<dyn Extractor> {
+        match self {
+            ExtractorBackend::YtDlp => Box::new(YtDlp::new(ytdlp_options)),
+            ExtractorBackend::Streamlink => Box::new(Streamlink),
+        }
+    }
+}
+
+/// `[ytdlp]` settings, unset fields fall back to the built-in defaults
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct YtDlpOptions {
+    /// Default: `{ browser = "firefox" }`
+    pub cookies: Option<Cookies>,
+    /// Default: `ipv4`
+    pub ip_family: Option<IpFamily>,
+    /// Default: `ffmpeg`
+    pub downloader: Option<String>,
+    /// Default: retry every 5 seconds
+    pub wait_for_video: Option<WaitForVideo>,
+    /// Default: `true`
+    pub mark_watched: Option<bool>,
+    pub extra_args: Option<Vec<String>>,
+}
+
+impl YtDlpOptions {
+    /// Fields set in `other` take precedence
+    pub fn merge(&self, other: &YtDlpOptions) -> YtDlpOptions {
+        YtDlpOptions {
+            cookies: other.cookies.clone().or(self.cookies.clone()),
+            ip_family: other.ip_family.clone().or(self.ip_family.clone()),
+            downloader: other.downloader.clone().or(self.downloader.clone()),
+            wait_for_video: other.wait_for_video.clone().or(self.wait_for_video.clone()),
+            mark_watched: other.mark_watched.or(self.mark_watched),
+            extra_args: other.extra_args.clone().or(self.extra_args.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Cookies {
+    None,
+    /// `BROWSER[+KEYRING][:PROFILE][::CONTAINER]` as understood by yt-dlp
+    Browser(String),
+    /// Netscape formatted cookies.txt
+    File(PathBuf),
+}
+
+impl Cookies {
+    fn args(&self) -> Vec<OsString> {
+        match self {
+            Cookies::None => vec![],
+            Cookies::Browser(browser) => vec!["--cookies-from-browser".into(), browser.into()],
+            Cookies::File(path) => vec!["--cookies".into(), path.into()],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    Any,
+    Ipv4,
+    Ipv6,
+}
+
+impl IpFamily {
+    fn args(&self) -> Vec<&str> {
+        match self {
+            IpFamily::Any => vec![],
+            IpFamily::Ipv4 => vec!["-4"],
+            IpFamily::Ipv6 => vec!["-6"],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum WaitForVideo {
+    /// `false` disables waiting, `true` uses the default interval
+    Enabled(bool),
+    Seconds(u64),
+    /// `MIN-MAX` seconds
+    Range(String),
+}
+
+impl WaitForVideo {
+    fn args(&self) -> Vec<String> {
+        match self {
+            WaitForVideo::Enabled(false) => vec!["--no-wait-for-video".to_string()],
+            WaitForVideo::Enabled(true) => vec!["--wait-for-video".to_string(), "5".to_string()],
+            WaitForVideo::Seconds(seconds) => {
+                vec!["--wait-for-video".to_string(), seconds.to_string()]
+            }
+            WaitForVideo::Range(range) => vec!["--wait-for-video".to_string(), range.clone()],
+        }
+    }
+}
+
+/// Section of a video or stream to play, in seconds from its start
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeRange {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl TimeRange {
+    /// Range from `--from`/`--to` style timestamps, `None` when neither is given
+    pub fn new(from: Option<&str>, to: Option<&str>) -> color_eyre::Result<Option<Self>> {
+        let parse = |timestamp: &str| {
+            parse_timestamp(timestamp)
+                .ok_or_else(|| color_eyre::eyre::eyre!("Invalid timestamp: {timestamp}"))
+        };
+        let range = TimeRange {
+            from: from.map(parse).transpose()?,
+            to: to.map(parse).transpose()?,
+        };
+        if let (Some(from), Some(to)) = (range.from, range.to)
+            && from >= to
+        {
+            return Err(color_eyre::eyre::eyre!(
+                "Range ends before it starts: {range}"
+            ));
+        }
+        Ok((range != TimeRange::default()).then_some(range))
+    }
+
+    /// Length of the range, `None` when it's open ended
+    pub fn duration(&self) -> Option<u64> {
+        self.to.map(|to| to - self.from.unwrap_or(0))
+    }
+}
+
+/// `START-END` with either side optional, e.g. `1:23:45-1:40:00` or `10:00-`
+impl FromStr for TimeRange {
+    type Err = color_eyre::eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s.split_once('-').unwrap_or((s, ""));
+        let (from, to) = (from.trim(), to.trim());
+        TimeRange::new(
+            (!from.is_empty()).then_some(from),
+            (!to.is_empty()).then_some(to),
+        )?
+        .ok_or_else(|| color_eyre::eyre::eyre!("Empty range"))
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let from = format_timestamp(self.from.unwrap_or(0));
+        match self.to {
+            Some(to) => write!(f, "{from}-{}", format_timestamp(to)),
+            None => write!(f, "{from}-end"),
+        }
+    }
+}
+
+pub trait Extractor: Send + Sync {
+    fn name(&self) -> &str;
+    /// Translate a yt-dlp style format into this extractor's quality syntax
+    fn format(&self, format: &str) -> String;
+    /// Command that writes the stream to its stdout, optionally limited to a
+    /// section, or starting a live stream from its beginning
+    fn command(
+        &self,
+        url: &str,
+        format: &str,
+        range: Option<&TimeRange>,
+        live_from_start: bool,
+    ) -> Command;
+    /// Command printing the formats offered for the url as JSON
+    fn formats_command(&self, url: &str) -> Command;
+    /// Formats from the output of `formats_command`
+    fn parse_formats(&self, output: &str) -> color_eyre::Result<Vec<FormatInfo>>;
+    /// Recognize a known failure in what the extractor wrote to stderr
+    fn classify(&self, _url: &str, _format: &str, _stderr: &str) -> Option<Error> {
+        None
+    }
+}
+
+/// A format offered for a stream, as listed by `formats`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatInfo {
+    /// What to pass to `-f`
+    pub id: String,
+    pub description: String,
+}
+
+pub struct YtDlp {
+    options: YtDlpOptions,
+}
+
+impl YtDlp {
+    pub fn new(options: YtDlpOptions) -> Self {
+        Self { options }
+    }
+
+    /// Arguments deciding how yt-dlp reaches the site
+    fn access_args(&self) -> Vec<OsString> {
+        let options = &self.options;
+        let ip_family = options.ip_family.as_ref().unwrap_or(&IpFamily::Ipv4).args();
+        let cookies = options
+            .cookies
+            .as_ref()
+            .unwrap_or(&Cookies::Browser("firefox".to_string()))
+            .args();
+        ip_family
+            .into_iter()
+            .map(OsString::from)
+            .chain(cookies)
+            .collect()
+    }
+}
+
+/// The fields of a yt-dlp `-J` format that make up its description
+#[derive(Debug, Deserialize)]
+struct YtDlpFormat {
+    format_id: String,
+    ext: Option<String>,
+    resolution: Option<String>,
+    fps: Option<f64>,
+    vcodec: Option<String>,
+    acodec: Option<String>,
+    tbr: Option<f64>,
+    format_note: Option<String>,
+}
+
+impl YtDlpFormat {
+    fn description(&self) -> String {
+        let codec = |codec: &Option<String>| codec.clone().filter(|codec| codec != "none");
+        [
+            self.resolution.clone(),
+            self.fps.map(|fps| format!("{fps}fps")),
+            self.ext.clone(),
+            codec(&self.vcodec),
+            codec(&self.acodec),
+            self.tbr.map(|tbr| format!("{}k", tbr.round())),
+            self.format_note.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+impl Extractor for YtDlp {
+    fn name(&self) -> &str {
+        "yt-dlp"
+    }
+
+    fn format(&self, format: &str) -> String {
+        format.to_string()
+    }
+
+    fn command(
+        &self,
+        url: &str,
+        format: &str,
+        range: Option<&TimeRange>,
+        live_from_start: bool,
+    ) -> Command {
+        let options = &self.options;
+        let mut ytdlp = Command::new("yt-dlp");
+        ytdlp
+            .arg(url)
+            .arg("-f")
+            .arg(self.format(format))
+            .arg("-q")
+            .args(self.access_args())
+            .args(match options.mark_watched.unwrap_or(true) {
+                true => vec!["--mark-watched"],
+                false => vec![],
+            })
+            .args(
+                options
+                    .wait_for_video
+                    .as_ref()
+                    .unwrap_or(&WaitForVideo::Enabled(true))
+                    .args(),
+            )
+            .arg("--downloader")
+            .arg(options.downloader.as_deref().unwrap_or("ffmpeg"))
+            .args(match range {
+                Some(range) => vec![
+                    "--download-sections".to_string(),
+                    format!(
+                        "*{}-{}",
+                        range.from.unwrap_or(0),
+                        range.to.map_or("inf".to_string(), |to| to.to_string())
+                    ),
+                ],
+                None => vec![],
+            })
+            .args(match live_from_start {
+                true => vec!["--live-from-start"],
+                false => vec![],
+            })
+            .args(options.extra_args.iter().flatten())
+            .arg("-o")
+            .arg("-");
+        ytdlp
+    }
+
+    fn formats_command(&self, url: &str) -> Command {
+        let mut ytdlp = Command::new("yt-dlp");
+        ytdlp
+            .arg(url)
+            .arg("-J")
+            .arg("--no-warnings")
+            .args(self.access_args())
+            .args(self.options.extra_args.iter().flatten());
+        ytdlp
+    }
+
+    fn parse_formats(&self, output: &str) -> color_eyre::Result<Vec<FormatInfo>> {
+        #[derive(Deserialize)]
+        struct Info {
+            #[serde(default)]
+            formats: Vec<YtDlpFormat>,
+        }
+
+        let info = serde_json::from_str::<Info>(output)?;
+        Ok(info
+            .formats
+            .iter()
+            .map(|format| FormatInfo {
+                id: format.format_id.clone(),
+                description: format.description(),
+            })
+            .collect())
+    }
+
+    fn classify(&self, url: &str, format: &str, stderr: &str) -> Option<Error> {
+        let stderr = stderr.to_lowercase();
+        let has = |patterns: &[&str]| patterns.iter().any(|pattern| stderr.contains(pattern));
+        let url = url.to_string();
+
+        if has(&["members-only", "available to this channel's members"]) {
+            Some(Error::MembersOnly(url))
+        } else if has(&[
+            "confirm your age",
+            "age-restricted",
+            "inappropriate for some users",
+        ]) {
+            Some(Error::AgeRestricted(url))
+        } else if has(&[
+            "available in your country",
+            "geo restriction",
+            "geo-restricted",
+        ]) {
+            Some(Error::GeoBlocked(url))
+        } else if has(&["requested format is not available"]) {
+            Some(Error::FormatUnavailable(url, format.to_string()))
+        } else if has(&[
+            "cookies are no longer valid",
+            "could not find firefox cookies database",
+            "could not find chrome cookies database",
+            "failed to decrypt with dpapi",
+            "sign in to confirm you're not a bot",
+        ]) {
+            Some(Error::CookiesExpired(url))
+        } else if has(&[
+            "is not currently live",
+            "is offline",
+            "this live event will begin",
+            "this live event has ended",
+            "premieres in",
+        ]) {
+            Some(Error::StreamOffline(url))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Streamlink;
+
+impl Extractor for Streamlink {
+    fn name(&self) -> &str {
+        "streamlink"
+    }
+
+    /// yt-dlp fallbacks (`a/b`) become streamlink fallbacks (`a,b`), and the
+    /// generic selectors are turned into streamlink quality names
+    fn format(&self, format: &str) -> String {
+        let mut qualities: Vec<String> = Vec::new();
+        for alternative in format.split('/') {
+            for quality in streamlink_qualities(alternative) {
+                if !qualities.contains(&quality) {
+                    qualities.push(quality);
+                }
+            }
+        }
+        qualities.join(",")
+    }
+
+    fn command(
+        &self,
+        url: &str,
+        format: &str,
+        range: Option<&TimeRange>,
+        live_from_start: bool,
+    ) -> Command {
+        let mut streamlink = Command::new("streamlink");
+        streamlink.arg("--loglevel").arg("error").arg("--stdout");
+        if url.contains("twitch.tv") && !live_from_start {
+            streamlink.arg("--twitch-low-latency");
+        }
+        if let Some(range) = range {
+            if let Some(from) = range.from {
+                streamlink.arg("--hls-start-offset").arg(from.to_string());
+            }
+            if let Some(duration) = range.duration() {
+                streamlink.arg("--hls-duration").arg(duration.to_string());
+            }
+        }
+        if live_from_start {
+            streamlink.arg("--hls-live-restart");
+        }
+        streamlink.arg(url).arg(self.format(format));
+        streamlink
+    }
+
+    fn formats_command(&self, url: &str) -> Command {
+        let mut streamlink = Command::new("streamlink");
+        streamlink.arg("--json").arg(url);
+        streamlink
+    }
+
+    /// Streams sorted from low to high quality, `best`/`worst` and the like last
+    fn parse_formats(&self, output: &str) -> color_eyre::Result<Vec<FormatInfo>> {
+        #[derive(Deserialize)]
+        struct Info {
+            #[serde(default)]
+            streams: HashMap<String, Stream>,
+        }
+        #[derive(Deserialize)]
+        struct Stream {
+            #[serde(rename = "type")]
+            kind: Option<String>,
+        }
+
+        let info = serde_json::from_str::<Info>(output)?;
+        let mut formats = info
+            .streams
+            .into_iter()
+            .map(|(name, stream)| FormatInfo {
+                description: stream.kind.unwrap_or_default(),
+                id: name,
+            })
+            .collect::<Vec<_>>();
+        formats.sort_by_key(|format| {
+            let (height, fps) = format.id.split_once('p').unwrap_or((&format.id, ""));
+            match height.parse::<u32>() {
+                Ok(height) => (height, fps.parse::<u32>().unwrap_or(0), String::new()),
+                Err(_) => (u32::MAX, 0, format.id.clone()),
+            }
+        });
+        Ok(formats)
+    }
+
+    fn classify(&self, url: &str, format: &str, stderr: &str) -> Option<Error> {
+        if stderr.contains("No playable streams found") {
+            Some(Error::StreamOffline(url.to_string()))
+        } else if stderr.contains("could not be found") {
+            Some(Error::FormatUnavailable(
+                url.to_string(),
+                format.to_string(),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+fn streamlink_qualities(alternative: &str) -> Vec<String> {
+    let alternative = alternative.trim();
+    match alternative {
+        "best" | "b" | "bv*+ba" | "bv+ba" | "bestvideo+bestaudio" => vec!["best".to_string()],
+        "worst" | "w" | "wv*+wa" | "worstvideo+worstaudio" => vec!["worst".to_string()],
+        "bestaudio" | "ba" | "worstaudio" | "wa" => vec!["audio_only".to_string()],
+        _ => match (max_height(alternative), limit(alternative, "fps")) {
+            (Some(height), Some(fps)) if fps <= 30 => {
+                vec![format!("{height}p30"), format!("{height}p")]
+            }
+            (Some(height), _) => vec![format!("{height}p60"), format!("{height}p")],
+            (None, _) => vec![alternative.to_string()],
+        },
+    }
+}
+
+/// Height limit of a yt-dlp filter like `bv*[height<=720]+ba`
+fn max_height(format: &str) -> Option<u32> {
+    limit(format, "height")
+}
+
+/// Upper limit the filter puts on a field, like `fps` in `[fps<=30]`
+fn limit(format: &str, field: &str) -> Option<u32> {
+    let (_, rest) = format
+        .split_once(&format!("{field}<="))
+        .or_else(|| format.split_once(&format!("{field}=")))?;
+    rest.chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ytdlp_options() {
+        let global = toml::from_str::<YtDlpOptions>(
+            r#"
+            cookies = { file = "/tmp/cookies.txt" }
+            ip_family = "ipv6"
+            extra_args = ["--no-part"]
+            "#,
+        )
+        .unwrap();
+        let twitch = toml::from_str::<YtDlpOptions>(
+            r#"
+            cookies = "none"
+            wait_for_video = false
+            "#,
+        )
+        .unwrap();
+
+        let ytdlp = YtDlp::new(global.merge(&twitch));
+        let range = "1:00-1:30".parse::<TimeRange>().unwrap();
+        let command = ytdlp.command("https://www.twitch.tv/example", "best", Some(&range), false);
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec![
+                "https://www.twitch.tv/example",
+                "-f",
+                "best",
+                "-q",
+                "-6",
+                "--mark-watched",
+                "--no-wait-for-video",
+                "--downloader",
+                "ffmpeg",
+                "--download-sections",
+                "*60-90",
+                "--no-part",
+                "-o",
+                "-",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_range() {
+        let range = "1:23:45-".parse::<TimeRange>().unwrap();
+        assert_eq!((range.from, range.to), (Some(5025), None));
+        assert_eq!(range.to_string(), "1:23:45-end");
+        assert_eq!(
+            TimeRange::new(None, Some("10:00"))
+                .unwrap()
+                .unwrap()
+                .duration(),
+            Some(600)
+        );
+        assert_eq!(TimeRange::new(None, None).unwrap(), None);
+        assert!("2:00-1:00".parse::<TimeRange>().is_err());
+        assert!("-".parse::<TimeRange>().is_err());
+
+        let streamlink = Streamlink.command(
+            "https://www.youtube.com/watch?v=abc",
+            "best",
+            Some(&range),
+            true,
+        );
+        assert_eq!(
+            streamlink.get_args().collect::<Vec<_>>(),
+            vec![
+                "--loglevel",
+                "error",
+                "--stdout",
+                "--hls-start-offset",
+                "5025",
+                "--hls-live-restart",
+                "https://www.youtube.com/watch?v=abc",
+                "best",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_ytdlp() {
+        let ytdlp = YtDlp::new(YtDlpOptions::default());
+        let url = "https://www.youtube.com/watch?v=abc";
+        #[rustfmt::skip]
+        let test_cases = vec![
+            ("ERROR: [youtube] abc: Join this channel to get access to members-only content like this video", Error::MembersOnly(url.into())),
+            ("ERROR: [youtube] abc: Sign in to confirm your age. This video may be inappropriate for some users.", Error::AgeRestricted(url.into())),
+            ("ERROR: [youtube] abc: The uploader has not made this video available in your country", Error::GeoBlocked(url.into())),
+            ("ERROR: [youtube] abc: Requested format is not available. Use --list-formats", Error::FormatUnavailable(url.into(), "best".into())),
+            ("WARNING: The provided YouTube account cookies are no longer valid.", Error::CookiesExpired(url.into())),
+            ("ERROR: [twitch:stream] example: The channel is not currently live", Error::StreamOffline(url.into())),
+        ];
+
+        for (stderr, expected) in test_cases {
+            let error = ytdlp.classify(url, "best", stderr);
+            assert_eq!(
+                error.map(|e| e.to_string()),
+                Some(expected.to_string()),
+                "Failed to classify: {stderr}"
+            );
+        }
+        assert!(
+            ytdlp
+                .classify(url, "best", "ERROR: something else")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let ytdlp = YtDlp::new(YtDlpOptions::default());
+        let formats = ytdlp
+            .parse_formats(
+                r#"{"id": "abc", "formats": [
+                    {"format_id": "233", "ext": "mp4", "resolution": "audio only", "vcodec": "none", "acodec": "mp4a.40.5"},
+                    {"format_id": "301", "ext": "mp4", "resolution": "1920x1080", "fps": 60, "vcodec": "avc1.64002a", "acodec": "mp4a.40.2", "tbr": 4500.4}
+                ]}"#,
+            )
+            .unwrap();
+        assert_eq!(formats[0].description, "audio only mp4 mp4a.40.5");
+        assert_eq!(
+            formats[1].description,
+            "1920x1080 60fps mp4 avc1.64002a mp4a.40.2 4500k"
+        );
+
+        let formats = Streamlink
+            .parse_formats(
+                r#"{"plugin": "twitch", "streams": {
+                    "best": {"type": "hls"}, "720p60": {"type": "hls"}, "160p": {"type": "hls"},
+                    "audio_only": {"type": "hls"}, "720p": {"type": "hls"}
+                }}"#,
+            )
+            .unwrap();
+        let ids = formats
+            .iter()
+            .map(|format| format.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["160p", "720p", "720p60", "audio_only", "best"]);
+    }
+
+    #[test]
+    fn test_streamlink_format() {
+        #[rustfmt::skip]
+        let test_cases = vec![
+            ("best", "best"),
+            ("720p60", "720p60"),
+            ("bv*[height<=720]+ba/best", "720p60,720p,best"),
+            ("bv*[height<=480][fps<=30]+ba", "480p30,480p"),
+            ("480p/480p30/worst", "480p,480p30,worst"),
+            ("bestaudio", "audio_only"),
+            ("720p60,best", "720p60,best"),
+        ];
+
+        for (format, expected) in test_cases {
+            assert_eq!(
+                Streamlink.format(format),
+                expected,
+                "Failed to map: {format}"
+            );
+        }
+    }
+}
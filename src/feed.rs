@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// How the live and upcoming streams of a YouTube channel are found
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Discovery {
+    /// `search.list`, 100 units per request
+    #[default]
+    Search,
+    /// The channel's public Atom feed, confirmed with one `videos.list`
+    Feed,
+}
+
+/// `[discovery]`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DiscoveryConfig {
+    pub strategy: Discovery,
+    /// Queried with `?channel_id=`
+    pub feed_url: String,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            strategy: Discovery::default(),
+            feed_url: String::from("https://www.youtube.com/feeds/videos.xml"),
+        }
+    }
+}
+
+/// Ids of the latest videos in the channel's feed, newest first
+pub async fn video_ids(
+    client: &reqwest::Client,
+    feed_url: &str,
+    channel_id: &str,
+) -> Result<Vec<String>, Error> {
+    let fail = |e| Error::YTFetchFeedFailed(channel_id.to_string(), e);
+    let feed = client
+        .get(feed_url)
+        .query(&[("channel_id", channel_id)])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(fail)?
+        .text()
+        .await
+        .map_err(fail)?;
+    Ok(parse_video_ids(&feed))
+}
+
+/// Id of the channel a page like `/c/name` belongs to, read from its
+/// canonical link
+pub async fn channel_page_id(
+    client: &reqwest::Client,
+    page_url: &str,
+) -> Result<Option<String>, Error> {
+    let fail = |e| Error::YTFetchChannelPageFailed(page_url.to_string(), e);
+    let page = client
+        .get(page_url)
+        // Skips the cookie consent page served in some regions
+        .header(reqwest::header::COOKIE, "SOCS=CAI")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(fail)?
+        .text()
+        .await
+        .map_err(fail)?;
+    Ok(parse_channel_id(&page))
+}
+
+fn parse_channel_id(page: &str) -> Option<String> {
+    let (_, rest) =
+        page.split_once(r#"<link rel="canonical" href="https://www.youtube.com/channel/"#)?;
+    let (channel_id, _) = rest.split_once('"')?;
+    (channel_id.starts_with("UC") && channel_id.len() == 24).then(|| channel_id.to_string())
+}
+
+/// The feed is simple enough that its entries' `<yt:videoId>` are all
+/// that's needed
+fn parse_video_ids(feed: &str) -> Vec<String> {
+    feed.split("<yt:videoId>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</yt:videoId>"))
+        .map(|(video_id, _)| video_id.trim().to_string())
+        .filter(|video_id| !video_id.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns="http://www.w3.org/2005/Atom">
+ <yt:channelId>UCabc</yt:channelId>
+ <title>Example</title>
+ <entry>
+  <id>yt:video:dQw4w9WgXcQ</id>
+  <yt:videoId>dQw4w9WgXcQ</yt:videoId>
+  <title>Karaoke</title>
+ </entry>
+ <entry>
+  <id>yt:video:cKZDdG9FTKY</id>
+  <yt:videoId>cKZDdG9FTKY</yt:videoId>
+  <title>Minecraft</title>
+ </entry>
+</feed>"#;
+
+    #[test]
+    fn test_parse_channel_id() {
+        let page = r#"<html><head><title>Example - YouTube</title>
+<link rel="canonical" href="https://www.youtube.com/channel/UCabcdefghijklmnopqrstuv">
+<meta property="og:url" content="https://www.youtube.com/channel/UCabcdefghijklmnopqrstuv">"#;
+        assert_eq!(
+            parse_channel_id(page).as_deref(),
+            Some("UCabcdefghijklmnopqrstuv")
+        );
+        // Pages that aren't a channel's, e.g. a consent page
+        assert_eq!(parse_channel_id("<html><head></head></html>"), None);
+        let video = r#"<link rel="canonical" href="https://www.youtube.com/watch?v=dQw4w9WgXcQ">"#;
+        assert_eq!(parse_channel_id(video), None);
+    }
+
+    #[tokio::test]
+    async fn test_feed_server() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/atom+xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{FEED}",
+                FEED.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request[..read]).to_string()
+        });
+
+        let feed_url = format!("http://{address}/feeds/videos.xml");
+        let ids = video_ids(&reqwest::Client::new(), &feed_url, "UCabc")
+            .await
+            .unwrap();
+        assert_eq!(ids, vec!["dQw4w9WgXcQ", "cKZDdG9FTKY"]);
+        assert!(
+            server
+                .await
+                .unwrap()
+                .starts_with("GET /feeds/videos.xml?channel_id=UCabc ")
+        );
+    }
+}
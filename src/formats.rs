@@ -0,0 +1,139 @@
+use std::process::Stdio;
+
+use clap::ArgMatches;
+use color_eyre::Section;
+use color_eyre::eyre::{OptionExt, eyre};
+
+use crate::context::Context;
+use crate::extractor::{Extractor, FormatInfo};
+use crate::preset;
+use crate::target::{
+    CliTarget, Platform, PlatformFlags, ToCliTarget, detect_platform, read_aliases,
+};
+use crate::twitch::{Twitch, get_twitch_username};
+use crate::youtube::{YouTube, YouTubeChannel};
+
+/// A stream the target resolved to
+pub struct ResolvedStream {
+    pub label: String,
+    pub platform: Platform,
+    pub url: String,
+}
+
+/// Ask the extractor which formats the url offers
+pub async fn list_formats(
+    extractor: &dyn Extractor,
+    url: &str,
+) -> color_eyre::Result<Vec<FormatInfo>> {
+    let output = tokio::process::Command::from(extractor.formats_command(url))
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if let Some(error) = extractor.classify(url, "", &stderr) {
+            let hint = error.hint().unwrap_or_default();
+            return Err(error).suggestion(hint);
+        }
+        let reason = stderr.lines().last().unwrap_or_default().to_string();
+        return Err(eyre!(
+            "{} failed to list formats: {reason}",
+            extractor.name()
+        ));
+    }
+    extractor.parse_formats(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Urls are taken as they are, channels resolve to their current live stream
+async fn resolve(
+    ctx: &Context,
+    target: &CliTarget,
+    platform_flags: &PlatformFlags,
+) -> color_eyre::Result<Vec<ResolvedStream>> {
+    let handles = match target {
+        CliTarget::Url(url) => match YouTubeChannel::from_url(url) {
+            Some(channel) => (Some(channel), None),
+            None => {
+                return Ok(vec![ResolvedStream {
+                    label: url.to_string(),
+                    platform: detect_platform(url),
+                    url: url.to_string(),
+                }]);
+            }
+        },
+        CliTarget::YoutubeChannelHandle(handle) => {
+            (Some(YouTubeChannel::Handle(handle.clone())), None)
+        }
+        CliTarget::MaubeAlias(alias) => {
+            let aliases = read_aliases(ctx);
+            let links = aliases
+                .get(alias)
+                .ok_or_eyre(format!("No alias found for: {alias}"))?;
+            let youtube = links
+                .youtube
+                .as_ref()
+                .filter(|_| platform_flags.is_all() || platform_flags.youtube)
+                .and_then(|link| link.channel.clone());
+            let twitch = links
+                .twitch
+                .as_ref()
+                .filter(|_| platform_flags.is_all() || platform_flags.twitch)
+                .and_then(|link| get_twitch_username(link.url.as_ref()).map(String::from));
+            (youtube, twitch)
+        }
+    };
+
+    let mut streams = Vec::new();
+    if let (Some(channel), _) = &handles {
+        let youtube = YouTube::new_youtube_client(&ctx.config).await?;
+        match youtube.live_url(channel, ctx).await? {
+            Some(url) => streams.push(ResolvedStream {
+                label: channel.to_string(),
+                platform: Platform::YouTube,
+                url,
+            }),
+            None => println!("Channel {channel} is not currently streaming"),
+        }
+    }
+    if let (_, Some(username)) = &handles {
+        let twitch = Twitch::new(&ctx.config).await?;
+        match twitch.is_live(username).await? {
+            true => streams.push(ResolvedStream {
+                label: username.clone(),
+                platform: Platform::Twitch,
+                url: format!("https://www.twitch.tv/{username}"),
+            }),
+            false => println!("Streamer {username} is not currently streaming"),
+        }
+    }
+    Ok(streams)
+}
+
+/// `streamdex formats TARGET [--youtube] [--twitch]`
+pub async fn run(ctx: &Context, args: &ArgMatches) -> color_eyre::Result<()> {
+    let target = args
+        .get_one::<String>("target")
+        .cloned()
+        .ok_or_eyre("Missing target")?
+        .to_target();
+
+    for stream in resolve(ctx, &target, &PlatformFlags::from_cli(args)).await? {
+        let extractor = ctx.config.extractor(&stream.platform);
+        match stream.label == stream.url {
+            true => println!("{} ({})", stream.url, stream.platform),
+            false => println!("{} ({}): {}", stream.label, stream.platform, stream.url),
+        }
+        let formats = list_formats(extractor.as_ref(), &stream.url).await?;
+        let width = formats
+            .iter()
+            .map(|format| format.id.len())
+            .max()
+            .unwrap_or_default();
+        for format in formats {
+            println!("  {:width$}  {}", format.id, format.description);
+        }
+        println!();
+    }
+    println!("Presets: {}", preset::names(&ctx.config.presets).join(", "));
+    Ok(())
+}
@@ -1,77 +1,75 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use google_youtube3::api::Video;
-use twitch_api::helix::streams::StreamType;
+use twitch_api::helix::streams::{Stream, StreamType};
 
 use crate::context::Context;
 use crate::error::{Error, FetchData};
-use crate::runner::watch_with_ytdlp_and_vlc;
+use crate::hooks::{self, Hook};
+use crate::runner::{StreamInfo, launch};
+use crate::target::Platform;
 use crate::twitch::Twitch;
-use crate::youtube::{LiveStatus, YouTube};
+use crate::youtube::{YouTube, YouTubeChannel};
 
 impl YouTube {
-    pub async fn handle_channel(&self, handle: &str, ctx: &Context) -> color_eyre::Result<()> {
-        let channel_id = self.get_channel_id(handle, &ctx).await?;
-
-        let ids = self.get_live_ids(&channel_id, LiveStatus::Live).await?;
+    pub async fn handle_channel(
+        &self,
+        channel: &YouTubeChannel,
+        ctx: &Context,
+    ) -> color_eyre::Result<()> {
+        let channel_id = self.get_channel_id(channel, ctx).await?;
 
-        if !ids.is_empty() {
-            let videos = self.get_videos_details(ids).await?;
-            let live_stream = self.get_one_that_actually_live(&videos)?;
+        let videos = self
+            .get_channel_videos(&channel_id, ctx.wait_for_live)
+            .await?;
 
-            if let Some(video) = live_stream {
-                self.handle_live(handle, video, ctx)?;
-            }
+        if let Some(video) = self.get_one_that_actually_live(&videos.live)? {
+            self.handle_live(channel, video, ctx).await?;
         } else if ctx.wait_for_live {
-            let ids = self.get_live_ids(&channel_id, LiveStatus::Upcoming).await?;
-            if ids.is_empty() {
-                println!("Channel {handle} is not live and has no upcoming streams.");
+            if videos.upcoming.is_empty() {
+                println!("Channel {channel} is not live and has no upcoming streams.");
                 return Ok(());
             }
 
-            let videos = self.get_videos_details(ids).await?;
-
-            if let Some(live) = self.get_one_that_actually_live(&videos)? {
-                self.handle_live(handle, live, ctx)?;
-            }
-
-            println!("Currently no live stream for youtube channel {handle}/{channel_id}");
+            println!("Currently no live stream for youtube channel {channel}/{channel_id}");
 
-            let upcoming_videos = self.get_ones_that_actually_upcoming(&videos)?;
-            self.handle_upcoming(handle, &upcoming_videos, ctx).await?;
+            let upcoming_videos = self.get_ones_that_actually_upcoming(&videos.upcoming)?;
+            self.handle_upcoming(channel, &upcoming_videos, ctx).await?;
         } else {
-            println!("Channel {handle} is not currently streaming");
+            println!("Channel {channel} is not currently streaming");
         }
         Ok(())
     }
 
-    pub fn handle_live(
+    pub async fn handle_live(
         &self,
-        _channel_handle: &str,
+        channel: &YouTubeChannel,
         video: Video,
         ctx: &Context,
     ) -> color_eyre::Result<()> {
         let video_id = video.id.ok_or(Error::NoDataFound(FetchData::VideoID))?;
-        let format = ctx
-            .format
-            .clone()
-            .unwrap_or(ctx.config.default_parameters.format.youtube.clone());
-        let print_format = ctx.print_command;
-
-        watch_with_ytdlp_and_vlc(
-            format!("https://www.youtube.com/watch?v={video_id}"),
-            format,
-            None,
-            print_format,
-        )?;
-
-        Ok(())
+        let url = format!("https://www.youtube.com/watch?v={video_id}");
+        let details = video.live_streaming_details;
+        let info = StreamInfo {
+            alias: ctx.alias().unwrap_or(channel.name()).to_string(),
+            platform: Platform::YouTube,
+            video_id: Some(video_id.clone()),
+            title: video.snippet.and_then(|s| s.title),
+            start_time: details.as_ref().and_then(|d| d.actual_start_time),
+            scheduled_start: details.and_then(|d| d.scheduled_start_time),
+        };
+        hooks::run(ctx, Hook::Live, &info, &url);
+
+        launch(ctx, url, &info, || async {
+            Ok(self.is_still_live(&video_id).await?)
+        })
+        .await
     }
 
     pub async fn handle_upcoming(
         &self,
-        channel_handle: &str,
+        channel: &YouTubeChannel,
         videos: &[Video],
         ctx: &Context,
     ) -> Result<(), Error> {
@@ -79,7 +77,10 @@ impl YouTube {
             .threshold
             .unwrap_or(ctx.config.default_parameters.threshold);
 
-        let closest = self.choose_closest_to_start(&videos, threshold);
+        let started_waiting = Instant::now();
+        let closest = self.choose_closest_to_start(videos, threshold);
+        // The stream being waited for, told to the wait timeout hook
+        let mut waiting_for = closest.clone();
 
         if let Some((video, start_time)) = closest {
             let video_id = video
@@ -95,7 +96,7 @@ impl YouTube {
             let hours_left: f64 = minutes_left as f64 / 60.0;
 
             if minutes_left < threshold {
-                self.handle_live(channel_handle, video.clone(), ctx)?;
+                self.handle_live(channel, video.clone(), ctx).await?;
                 return Ok(());
             }
 
@@ -112,41 +113,57 @@ impl YouTube {
         let interval = ctx
             .interval
             .unwrap_or(ctx.config.default_parameters.interval);
-        let channel_id = self.get_channel_id(channel_handle, ctx).await?;
+        let channel_id = self.get_channel_id(channel, ctx).await?;
 
         let mut first_loop = true;
 
         loop {
             if !first_loop {
-                let live_ids = self.get_live_ids(&channel_id, LiveStatus::Live).await?;
-                if !live_ids.is_empty() {
-                    let videos = self.get_videos_details(live_ids).await?;
-                    if let Some(live) = self.get_one_that_actually_live(&videos)? {
-                        self.handle_live(channel_handle, live, ctx)?;
-                        break;
-                    }
+                let videos = self.get_channel_videos(&channel_id, true).await?;
+                if let Some(live) = self.get_one_that_actually_live(&videos.live)? {
+                    self.handle_live(channel, live, ctx).await?;
+                    break;
                 }
 
-                let upcoming_ids = self.get_live_ids(&channel_id, LiveStatus::Upcoming).await?;
-                if !upcoming_ids.is_empty() {
-                    let videos = self.get_videos_details(upcoming_ids).await?;
-                    let actually_upcoming = self.get_ones_that_actually_upcoming(&videos)?;
-                    if let Some((video, start_time)) =
-                        self.choose_closest_to_start(&actually_upcoming, threshold)
-                    {
-                        let minutes_left =
-                            start_time.signed_duration_since(Utc::now()).num_minutes();
-                        if minutes_left < threshold {
-                            self.handle_live(channel_handle, video, ctx)?;
-                            break;
-                        }
+                let actually_upcoming = self.get_ones_that_actually_upcoming(&videos.upcoming)?;
+                if let Some((video, start_time)) =
+                    self.choose_closest_to_start(&actually_upcoming, threshold)
+                {
+                    let minutes_left = start_time.signed_duration_since(Utc::now()).num_minutes();
+                    if minutes_left < threshold {
+                        self.handle_live(channel, video, ctx).await?;
+                        break;
                     }
+                    waiting_for = Some((video, start_time));
                 }
             }
 
-            println!("Waiting {interval} minutes until fetching new data...");
+            if wait_timed_out(ctx, started_waiting) {
+                let info = StreamInfo {
+                    alias: ctx.alias().unwrap_or(channel.name()).to_string(),
+                    platform: Platform::YouTube,
+                    video_id: waiting_for.as_ref().and_then(|(video, _)| video.id.clone()),
+                    title: waiting_for
+                        .as_ref()
+                        .and_then(|(video, _)| video.snippet.as_ref())
+                        .and_then(|s| s.title.clone()),
+                    start_time: None,
+                    scheduled_start: waiting_for.as_ref().map(|(_, start_time)| *start_time),
+                };
+                println!("Stopped waiting for {channel} to go live");
+                hooks::run(ctx, Hook::WaitTimeout, &info, &channel.url());
+                break;
+            }
+
+            let wait = self.poll_interval(Duration::from_mins(interval));
+            println!(
+                "Waiting {} minutes until fetching new data...",
+                wait.as_secs().div_ceil(60)
+            );
 
-            tokio::time::sleep(Duration::from_mins(interval)).await;
+            if !ctx.supervisor.sleep(wait).await {
+                break;
+            }
 
             first_loop = false;
         }
@@ -162,7 +179,7 @@ impl<'a> Twitch<'a> {
         if !streams.is_empty() {
             for stream in &streams {
                 if stream.type_ == StreamType::Live {
-                    self.handle_live(username, ctx).await?;
+                    self.handle_live(username, stream, ctx).await?;
                     break;
                 }
             }
@@ -177,19 +194,28 @@ impl<'a> Twitch<'a> {
         Ok(())
     }
 
-    pub async fn handle_live(&self, username: &str, ctx: &Context) -> color_eyre::Result<()> {
+    pub async fn handle_live(
+        &self,
+        username: &str,
+        stream: &Stream,
+        ctx: &Context,
+    ) -> color_eyre::Result<()> {
         let url = format!("https://www.twitch.tv/{username}");
+        let info = StreamInfo {
+            alias: ctx.alias().unwrap_or(username).to_string(),
+            platform: Platform::Twitch,
+            video_id: Some(stream.id.to_string()),
+            title: Some(stream.title.clone()),
+            start_time: DateTime::parse_from_rfc3339(stream.started_at.as_str())
+                .ok()
+                .map(|start| start.to_utc()),
+            scheduled_start: None,
+        };
+        hooks::run(ctx, Hook::Live, &info, &url);
 
         println!("Watching {url}...");
 
-        let format = ctx
-            .format
-            .clone()
-            .unwrap_or(ctx.config.default_parameters.format.twitch.clone());
-        let print_format = ctx.print_command;
-
-        watch_with_ytdlp_and_vlc(url, format, None, print_format)?;
-        Ok(())
+        launch(ctx, url, &info, || self.is_live(username)).await
     }
 
     pub async fn handle_wait_stream(
@@ -203,15 +229,38 @@ impl<'a> Twitch<'a> {
             .interval
             .unwrap_or(ctx.config.default_parameters.interval);
 
+        let started_waiting = Instant::now();
+
         'outer: loop {
+            if wait_timed_out(ctx, started_waiting) {
+                let info = StreamInfo {
+                    alias: ctx.alias().unwrap_or(username).to_string(),
+                    platform: Platform::Twitch,
+                    video_id: None,
+                    title: None,
+                    start_time: None,
+                    scheduled_start: None,
+                };
+                println!("Stopped waiting for {username} to go live");
+                hooks::run(
+                    ctx,
+                    Hook::WaitTimeout,
+                    &info,
+                    &format!("https://www.twitch.tv/{username}"),
+                );
+                break;
+            }
+
             println!("Waiting {interval} minutes until fetching new data...");
-            tokio::time::sleep(Duration::from_mins(interval)).await;
+            if !ctx.supervisor.sleep(Duration::from_mins(interval)).await {
+                break;
+            }
 
             let streams = self.get_streams(username).await?;
 
             for stream in streams {
                 if stream.type_ == StreamType::Live {
-                    self.handle_live(username, ctx).await?;
+                    self.handle_live(username, &stream, ctx).await?;
                     break 'outer;
                 }
             }
@@ -219,3 +268,9 @@ impl<'a> Twitch<'a> {
         Ok(())
     }
 }
+
+/// Whether `wait_timeout` minutes have passed since waiting started
+fn wait_timed_out(ctx: &Context, started: Instant) -> bool {
+    let timeout = ctx.config.default_parameters.wait_timeout;
+    timeout > 0 && started.elapsed() >= Duration::from_mins(timeout)
+}
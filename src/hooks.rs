@@ -0,0 +1,152 @@
+use std::process::Stdio;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::context::Context;
+use crate::runner::StreamInfo;
+
+/// `[hooks]`, shell commands run when something happens to a stream. The
+/// stream is described by `STREAMDEX_*` environment variables.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// The stream was found live, before anything is launched
+    pub on_live: Option<String>,
+    pub on_play_start: Option<String>,
+    /// Playback ended, also after giving up on reconnecting
+    pub on_play_end: Option<String>,
+    /// Stopped waiting after `wait_timeout` minutes
+    pub on_wait_timeout: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hook {
+    Live,
+    PlayStart,
+    PlayEnd,
+    WaitTimeout,
+}
+
+impl Hook {
+    fn name(&self) -> &'static str {
+        match self {
+            Hook::Live => "live",
+            Hook::PlayStart => "play_start",
+            Hook::PlayEnd => "play_end",
+            Hook::WaitTimeout => "wait_timeout",
+        }
+    }
+}
+
+impl HooksConfig {
+    fn command(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::Live => self.on_live.as_deref(),
+            Hook::PlayStart => self.on_play_start.as_deref(),
+            Hook::PlayEnd => self.on_play_end.as_deref(),
+            Hook::WaitTimeout => self.on_wait_timeout.as_deref(),
+        }
+    }
+}
+
+/// Start the hook's command in the background, nothing happens without one
+/// or when only printing what would be launched
+pub fn run(ctx: &Context, hook: Hook, info: &StreamInfo, url: &str) {
+    let Some(command) = ctx.config.hooks.command(hook) else {
+        return;
+    };
+    if ctx.print_command || ctx.dry_run.is_some() {
+        return;
+    }
+
+    let quiet = ctx.supervisor.is_quiet();
+    let output = || match quiet {
+        true => Stdio::null(),
+        false => Stdio::inherit(),
+    };
+    #[cfg(unix)]
+    let mut shell = tokio::process::Command::new("sh");
+    #[cfg(unix)]
+    shell.arg("-c");
+    #[cfg(not(unix))]
+    let mut shell = tokio::process::Command::new("cmd");
+    #[cfg(not(unix))]
+    shell.arg("/C");
+
+    let child = shell
+        .arg(command)
+        .envs(env(hook, info, url))
+        .stdin(Stdio::null())
+        .stdout(output())
+        .stderr(output())
+        .spawn();
+    let name = hook.name();
+    match child {
+        Ok(mut child) => {
+            tokio::spawn(async move {
+                if let Ok(status) = child.wait().await
+                    && !status.success()
+                    && !quiet
+                {
+                    eprintln!("Hook on_{name} exited with status: {status}");
+                }
+            });
+        }
+        Err(e) if !quiet => eprintln!("Failed to run hook on_{name}: {e}"),
+        Err(_) => (),
+    }
+}
+
+/// What the hook gets to know about the stream, unknown values are left empty
+fn env(hook: Hook, info: &StreamInfo, url: &str) -> Vec<(&'static str, String)> {
+    let time = |time: Option<DateTime<Utc>>| time.map(|time| time.to_rfc3339()).unwrap_or_default();
+    vec![
+        ("STREAMDEX_EVENT", hook.name().to_string()),
+        ("STREAMDEX_ALIAS", info.alias.clone()),
+        ("STREAMDEX_PLATFORM", info.platform.to_string()),
+        ("STREAMDEX_URL", url.to_string()),
+        (
+            "STREAMDEX_VIDEO_ID",
+            info.video_id.clone().unwrap_or_default(),
+        ),
+        ("STREAMDEX_TITLE", info.title.clone().unwrap_or_default()),
+        ("STREAMDEX_SCHEDULED_START", time(info.scheduled_start)),
+        ("STREAMDEX_ACTUAL_START", time(info.start_time)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::Platform;
+
+    #[test]
+    fn test_env() {
+        let info = StreamInfo {
+            alias: String::from("sora"),
+            platform: Platform::YouTube,
+            video_id: Some(String::from("abc")),
+            title: Some(String::from("Karaoke")),
+            start_time: None,
+            scheduled_start: "2024-05-01T12:00:00Z".parse().ok(),
+        };
+        let env = env(Hook::Live, &info, "https://www.youtube.com/watch?v=abc");
+        let get = |key: &str| {
+            env.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("STREAMDEX_EVENT"), Some("live"));
+        assert_eq!(get("STREAMDEX_PLATFORM"), Some("YouTube"));
+        assert_eq!(
+            get("STREAMDEX_URL"),
+            Some("https://www.youtube.com/watch?v=abc")
+        );
+        assert_eq!(
+            get("STREAMDEX_SCHEDULED_START"),
+            Some("2024-05-01T12:00:00+00:00")
+        );
+        assert_eq!(get("STREAMDEX_ACTUAL_START"), Some(""));
+    }
+}
@@ -1,25 +1,41 @@
-use std::fs;
 use std::io::stdout;
 
+use color_eyre::eyre::eyre;
 use crossterm::ExecutableCommand;
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 
 use crate::config::Config;
 use crate::context::Context;
-use crate::runner::watch_with_ytdlp_and_vlc;
-use crate::target::{Aliases, CliTarget, PlatformFlags};
+use crate::extractor::TimeRange;
+use crate::multiview::Geometry;
+use crate::runner::{StreamInfo, launch};
+use crate::target::{Aliases, CliTarget, Platform, PlatformFlags, read_aliases};
 use crate::tui::Tui;
 use crate::twitch::{Twitch, get_twitch_username};
 use crate::utils::extract_youtube_id_from_url;
-use crate::youtube::YouTube;
+use crate::youtube::{YouTube, YouTubeChannel};
 
+mod cache;
 mod cli;
 mod config;
 mod context;
 mod error;
 mod event;
+mod extractor;
+mod feed;
+mod formats;
 mod handler;
+mod hooks;
+mod multiview;
+mod plan;
+mod player;
+mod preset;
+mod quota;
+mod record;
+mod remote;
+mod resume;
 mod runner;
+mod supervisor;
 mod target;
 mod tui;
 mod twitch;
@@ -32,73 +48,222 @@ async fn main() -> color_eyre::Result<()> {
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
 
     let cli = cli::parse();
+    if let Some(("remote", args)) = cli.subcommand() {
+        return remote::run(args).await;
+    }
+
     let config = Config::get();
     let ctx = Context::new(config, &cli)?;
 
+    match cli.subcommand() {
+        Some(("formats", args)) => return formats::run(&ctx, args).await,
+        Some(("cache", args)) => return cache::run(&ctx, args),
+        Some(("status", _)) => {
+            quota::status(&ctx);
+            return Ok(());
+        }
+        _ => (),
+    }
+
     let tui = cli.get_flag("tui");
 
     if tui {
+        // Set up before the terminal is taken over, they may print
+        let youtube = YouTube::new_youtube_client(&ctx.config).await?;
+        let twitch = Twitch::new(&ctx.config)
+            .await
+            .inspect_err(|e| eprintln!("Dropped Twitch streams won't be reconnected: {e}"))
+            .ok();
+
+        ctx.supervisor.set_quiet();
         let terminal = ratatui::init();
         stdout().execute(EnableMouseCapture)?;
-        let mut tui = Tui::new(&ctx)?;
+        let mut tui = Tui::new(&ctx, youtube, twitch)?;
         tui.run(terminal).await?;
         stdout().execute(DisableMouseCapture)?;
         ratatui::restore();
     } else {
+        ctx.supervisor.handle_signals()?;
+
         let youtube = YouTube::new_youtube_client(&ctx.config).await?;
 
         let twitch = Twitch::new(&ctx.config).await?;
 
-        let target = ctx.target.as_ref().expect("Required by clap");
-
-        match target {
-            CliTarget::Url(url) => {
-                if let Some(_video_id) = extract_youtube_id_from_url(&url) {
-                    // let videos = youtube.get_videos_details(vec![video_id]).await?;
-                    // let video = videos
-                    //     .first()
-                    //     .ok_or(anyhow::anyhow!(
-                    //         "Should get one video from extracted youtube video id"
-                    //     ))?
-                    //     .clone();
-                    let format = ctx
-                        .format
-                        .clone()
-                        .unwrap_or(ctx.config.default_parameters.format.youtube);
-                    let print_command = ctx.print_command;
-                    watch_with_ytdlp_and_vlc(url.to_string(), format, None, print_command)?;
+        let platform_flags = PlatformFlags::from_cli(&cli);
+
+        if ctx.multiview {
+            let aliases = read_aliases(&ctx);
+            let slots = ctx
+                .targets
+                .iter()
+                .map(|target| slot_count(target, &aliases, &platform_flags))
+                .collect::<Vec<_>>();
+            let mut geometries = ctx.config.multiview.grid(slots.iter().sum()).into_iter();
+
+            // Each target runs on its own, one failing or closing leaves the others running
+            let sessions = ctx.targets.iter().zip(slots).map(|(target, slots)| {
+                let mut ctx = ctx.clone();
+                ctx.target = Some(target.clone());
+                let geometries = geometries.by_ref().take(slots).collect::<Vec<_>>();
+                let (youtube, twitch, platform_flags) = (&youtube, &twitch, &platform_flags);
+                async move {
+                    watch_target(ctx, &geometries, youtube, twitch, platform_flags)
+                        .await
+                        .inspect_err(|e| eprintln!("{target}: {e}"))
                 }
+            });
+            let failed = futures::future::join_all(sessions)
+                .await
+                .into_iter()
+                .filter(Result::is_err)
+                .count();
+            if failed > 0 {
+                return Err(eyre!("{failed} of {} targets failed", ctx.targets.len()));
             }
-            CliTarget::YoutubeChannelHandle(handle) => {
-                youtube.handle_channel(&handle, &ctx).await?;
+        } else {
+            watch_target(ctx, &[], &youtube, &twitch, &platform_flags).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Player windows a target needs, one per platform of an alias
+fn slot_count(target: &CliTarget, aliases: &Aliases, platform_flags: &PlatformFlags) -> usize {
+    match target {
+        CliTarget::MaubeAlias(alias) => aliases
+            .get(alias)
+            .map(|links| {
+                let youtube =
+                    links.youtube.is_some() && (platform_flags.is_all() || platform_flags.youtube);
+                let twitch =
+                    links.twitch.is_some() && (platform_flags.is_all() || platform_flags.twitch);
+                (youtube as usize + twitch as usize).max(1)
+            })
+            .unwrap_or(1),
+        _ => 1,
+    }
+}
+
+/// Watch `ctx.target`, placing its player windows at `geometries` in order
+async fn watch_target(
+    mut ctx: Context,
+    geometries: &[Geometry],
+    youtube: &YouTube,
+    twitch: &Twitch<'_>,
+    platform_flags: &PlatformFlags,
+) -> color_eyre::Result<()> {
+    let target = ctx.target.clone().expect("Required by clap");
+    ctx.geometry = geometries.first().copied();
+
+    match &target {
+        CliTarget::Url(url) => {
+            if let Some(video_id) = extract_youtube_id_from_url(url) {
+                let snippet = match youtube.get_videos_details(vec![video_id.clone()]).await {
+                    Ok(videos) => videos.into_iter().next().and_then(|video| video.snippet),
+                    Err(e) if ctx.record || ctx.record_only => return Err(e.into()),
+                    // Only needed for the recording's name, and to tell a broadcast apart
+                    Err(_) => None,
+                };
+                ctx.vod = snippet
+                    .as_ref()
+                    .and_then(|s| s.live_broadcast_content.as_deref())
+                    .is_none_or(|content| content == "none");
+                if ctx.vod
+                    && ctx.range.is_none()
+                    && !ctx.multiview
+                    && !ctx.print_command
+                    && ctx.dry_run.is_none()
+                    && let Some(position) = resume::position(&ctx.config.resume, &video_id)
+                {
+                    let name = snippet
+                        .as_ref()
+                        .and_then(|s| s.title.as_deref())
+                        .unwrap_or(&video_id);
+                    if resume::ask(name, position).await {
+                        ctx.range = Some(TimeRange {
+                            from: Some(position),
+                            to: None,
+                        });
+                    }
+                }
+                let info = StreamInfo {
+                    alias: snippet
+                        .as_ref()
+                        .and_then(|s| s.channel_title.clone())
+                        .unwrap_or(video_id.clone()),
+                    platform: Platform::YouTube,
+                    video_id: Some(video_id.clone()),
+                    title: snippet.as_ref().and_then(|s| s.title.clone()),
+                    start_time: snippet.and_then(|s| s.published_at),
+                    scheduled_start: None,
+                };
+                launch(&ctx, url.to_string(), &info, || async {
+                    Ok(youtube.is_still_live(&video_id).await?)
+                })
+                .await?;
+            } else if let Some(channel) = YouTubeChannel::from_url(url) {
+                youtube.handle_channel(&channel, &ctx).await?;
             }
-            CliTarget::MaubeAlias(alias) => {
-                let aliases_string =
-                    fs::read_to_string(&ctx.config.new_aliases).unwrap_or("{}".to_string());
-                let aliases = toml::from_str::<Aliases>(&aliases_string).unwrap_or_default();
+        }
+        CliTarget::YoutubeChannelHandle(handle) => {
+            youtube
+                .handle_channel(&YouTubeChannel::Handle(handle.clone()), &ctx)
+                .await?;
+        }
+        CliTarget::MaubeAlias(alias) => {
+            let aliases = read_aliases(&ctx);
 
-                let links = aliases.get(alias);
+            let links = aliases.get(alias);
 
-                if let Some(links) = links {
-                    let platform_flags = PlatformFlags::from_cli(&cli);
+            if let Some(links) = links {
+                if links.record_only {
+                    ctx.set_record_only();
+                }
+                if ctx.format.is_none() {
+                    ctx.format = links.format.clone();
+                }
+                ctx.audio_only |= links.audio_only;
+
+                let watch_youtube =
+                    links.youtube.is_some() && (platform_flags.is_all() || platform_flags.youtube);
+                let mut twitch_ctx = ctx.clone();
+                if watch_youtube {
+                    twitch_ctx.geometry = geometries.get(1).copied().or(ctx.geometry);
+                }
 
-                    if let Some(youtube_link) = &links.youtube {
-                        if platform_flags.is_all() || platform_flags.youtube {
-                            youtube.handle_channel(&youtube_link.username, &ctx).await?;
-                        }
+                let youtube_session = async {
+                    if let Some(channel) = links
+                        .youtube
+                        .as_ref()
+                        .and_then(|link| link.channel.as_ref())
+                        && watch_youtube
+                    {
+                        youtube.handle_channel(channel, &ctx).await?;
                     }
-                    if let Some(twitch_link) = &links.twitch {
-                        if platform_flags.is_all() || platform_flags.twitch {
-                            if let Some(username) =
-                                get_twitch_username(&twitch_link.url.to_string())
-                            {
-                                twitch.handle_streamer(username, &ctx).await?;
-                            }
-                        }
+                    color_eyre::Result::<()>::Ok(())
+                };
+                let twitch_session = async {
+                    if let Some(twitch_link) = &links.twitch
+                        && (platform_flags.is_all() || platform_flags.twitch)
+                        && let Some(username) = get_twitch_username(twitch_link.url.as_ref())
+                    {
+                        twitch.handle_streamer(username, &twitch_ctx).await?;
                     }
+                    color_eyre::Result::<()>::Ok(())
+                };
+                if ctx.multiview {
+                    // Side by side, one ending or failing doesn't stop the other
+                    let (youtube_result, twitch_result) =
+                        tokio::join!(youtube_session, twitch_session);
+                    youtube_result.and(twitch_result)?;
                 } else {
-                    println!("No alias found for: {}", alias);
+                    // One player at a time, YouTube first
+                    youtube_session.await?;
+                    twitch_session.await?;
                 }
+            } else {
+                println!("No alias found for: {}", alias);
             }
         }
     }
@@ -0,0 +1,96 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// `[multiview]`, the area the player windows are tiled over
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MultiviewConfig {
+    pub width: u32,
+    pub height: u32,
+    /// Top left corner of the area, e.g. to use a second monitor
+    pub x: i32,
+    pub y: i32,
+    /// Columns of the grid, 0 picks as many as needed for a square-ish grid
+    pub columns: usize,
+}
+
+impl Default for MultiviewConfig {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            x: 0,
+            y: 0,
+            columns: 0,
+        }
+    }
+}
+
+impl MultiviewConfig {
+    /// One window per stream, filled in row by row
+    pub fn grid(&self, count: usize) -> Vec<Geometry> {
+        if count == 0 {
+            return vec![];
+        }
+        let columns = match self.columns {
+            0 => (count as f64).sqrt().ceil() as usize,
+            columns => columns.min(count),
+        };
+        let rows = count.div_ceil(columns);
+        let width = self.width / columns as u32;
+        let height = self.height / rows as u32;
+        (0..count)
+            .map(|slot| Geometry {
+                x: self.x + ((slot % columns) as u32 * width) as i32,
+                y: self.y + ((slot / columns) as u32 * height) as i32,
+                width,
+                height,
+            })
+            .collect()
+    }
+}
+
+/// Size and position of a player window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// X11 style `WIDTHxHEIGHT+X+Y`, as mpv takes it
+impl Display for Geometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}{:+}{:+}", self.width, self.height, self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let config = MultiviewConfig::default();
+        let grid = config.grid(3);
+        #[rustfmt::skip]
+        let expected = [
+            "960x540+0+0", "960x540+960+0",
+            "960x540+0+540",
+        ];
+        assert_eq!(
+            grid.iter().map(|g| g.to_string()).collect::<Vec<_>>(),
+            expected
+        );
+
+        let config = MultiviewConfig {
+            x: 1920,
+            columns: 3,
+            ..Default::default()
+        };
+        assert_eq!(config.grid(2)[1].to_string(), "960x1080+2880+0");
+        assert!(config.grid(0).is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::context::Context;
+use crate::runner::{Pipeline, StreamInfo};
+use crate::target::Platform;
+
+/// Output of `--dry-run`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DryRun {
+    Text,
+    Json,
+}
+
+impl DryRun {
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "text" => Some(DryRun::Text),
+            "json" => Some(DryRun::Json),
+            _ => None,
+        }
+    }
+}
+
+/// How a target was resolved and what would be launched for it
+#[derive(Debug, Serialize)]
+pub struct LaunchPlan {
+    /// Target as given on the command line
+    pub target: Option<String>,
+    pub alias: String,
+    pub platform: Platform,
+    pub url: String,
+    pub video_id: Option<String>,
+    pub title: Option<String>,
+    /// Format passed to the extractor, translated to its own syntax
+    pub format: String,
+    pub range: Option<String>,
+    pub live_from_start: bool,
+    pub audio_only: bool,
+    pub extractor: Vec<String>,
+    pub player: Option<Vec<String>>,
+    pub record: Option<PathBuf>,
+}
+
+impl LaunchPlan {
+    pub fn new(ctx: &Context, pipeline: &Pipeline, info: &StreamInfo) -> Self {
+        Self {
+            target: ctx.target.as_ref().map(|target| target.to_string()),
+            alias: info.alias.clone(),
+            platform: info.platform,
+            url: pipeline.url.clone(),
+            video_id: info.video_id.clone(),
+            title: info.title.clone(),
+            format: pipeline.extractor.format(&pipeline.format),
+            range: pipeline.range.map(|range| range.to_string()),
+            live_from_start: pipeline.live_from_start,
+            audio_only: pipeline.audio_only,
+            extractor: argv(&pipeline.extractor_command()),
+            player: pipeline.player_command().map(|command| argv(&command)),
+            record: pipeline.record.clone(),
+        }
+    }
+
+    /// `--dry-run` prints the whole plan, `--print-command` only the command
+    pub fn print(&self, dry_run: Option<DryRun>) -> color_eyre::Result<()> {
+        match dry_run {
+            Some(DryRun::Json) => println!("{}", serde_json::to_string(self)?),
+            Some(DryRun::Text) => print!("{}", self.text()),
+            None => println!("{}", self.shell_command()),
+        }
+        Ok(())
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        if let Some(target) = &self.target {
+            text.push_str(&format!("# target:   {target}\n"));
+        }
+        text.push_str(&format!("# resolved: {} ({})\n", self.alias, self.platform));
+        text.push_str(&format!("# url:      {}\n", self.url));
+        if let Some(title) = &self.title {
+            text.push_str(&format!("# title:    {title}\n"));
+        }
+        text.push_str(&format!("# format:   {}\n", self.format));
+        if let Some(range) = &self.range {
+            text.push_str(&format!("# range:    {range}\n"));
+        }
+        if self.live_from_start {
+            text.push_str("# playing from the start of the live stream\n");
+        }
+        if self.audio_only {
+            text.push_str("# audio only\n");
+        }
+        text.push_str(&self.shell_command());
+        text.push('\n');
+        text
+    }
+
+    /// The pipeline as a shell command that does the same as the runner
+    pub fn shell_command(&self) -> String {
+        let mut command = shell_join(&self.extractor);
+        match (&self.player, &self.record) {
+            (Some(player), Some(record)) => {
+                command.push_str(&format!(
+                    " | tee -a {} | {}",
+                    shell_quote(&record.to_string_lossy()),
+                    shell_join(player)
+                ));
+            }
+            (Some(player), None) => {
+                command.push_str(" | ");
+                command.push_str(&shell_join(player));
+            }
+            (None, Some(record)) => {
+                command.push_str(" >> ");
+                command.push_str(&shell_quote(&record.to_string_lossy()));
+            }
+            (None, None) => (),
+        }
+        command
+    }
+}
+
+fn argv(command: &Command) -> Vec<String> {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn shell_join(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quote an argument for POSIX shells, plain words are left as they are
+pub fn shell_quote(arg: &str) -> Cow<'_, str> {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    match plain {
+        true => Cow::Borrowed(arg),
+        false => Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        #[rustfmt::skip]
+        let test_cases = vec![
+            ("best", "best"),
+            ("https://www.youtube.com/watch?v=abc", "'https://www.youtube.com/watch?v=abc'"),
+            ("bv*[height<=720]+ba/best", "'bv*[height<=720]+ba/best'"),
+            ("it's live", r"'it'\''s live'"),
+            ("", "''"),
+        ];
+
+        for (arg, expected) in test_cases {
+            assert_eq!(shell_quote(arg), expected, "Failed to quote: {arg}");
+        }
+    }
+
+    #[test]
+    fn test_shell_command() {
+        let plan = LaunchPlan {
+            target: Some(String::from("sora")),
+            alias: String::from("sora"),
+            platform: Platform::YouTube,
+            url: String::from("https://www.youtube.com/watch?v=abc"),
+            video_id: Some(String::from("abc")),
+            title: None,
+            format: String::from("best"),
+            range: None,
+            live_from_start: false,
+            audio_only: false,
+            extractor: vec!["yt-dlp".into(), "-f".into(), "best".into(), "-".into()],
+            player: Some(vec!["vlc".into(), "-".into()]),
+            record: Some(PathBuf::from("sora/my stream.ts")),
+        };
+        assert_eq!(
+            plan.shell_command(),
+            "yt-dlp -f best - | tee -a 'sora/my stream.ts' | vlc -"
+        );
+    }
+}
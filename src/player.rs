@@ -0,0 +1,257 @@
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+use serde::{Deserialize, Serialize};
+
+use crate::multiview::Geometry;
+
+/// Player selection as written in the config file
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerBackend {
+    #[default]
+    Vlc,
+    Mpv,
+    Ffplay,
+    /// Command template, `{input}` is replaced with the stream source (stdin)
+    Custom(String),
+}
+
+impl PlayerBackend {
+    pub fn build(&self) -> Box<dyn Player> {
+        match self {
+            PlayerBackend::Vlc => Box::new(Vlc),
+            PlayerBackend::Mpv => Box::new(Mpv),
+            PlayerBackend::Ffplay => Box::new(Ffplay),
+            PlayerBackend::Custom(template) => Box::new(CustomPlayer::new(template)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PlayerExit {
+    /// Closed by the user or reached the end of the stream
+    Closed,
+    /// Failed to start or to play the stream
+    Failed(String),
+}
+
+pub trait Player: Send + Sync {
+    fn name(&self) -> &str;
+    /// Command that reads the stream from its stdin
+    fn command(&self) -> Command;
+    fn interpret_exit(&self, status: ExitStatus) -> PlayerExit;
+    /// Arguments that keep the player from opening a video window
+    fn audio_only_args(&self) -> Vec<&str> {
+        vec![]
+    }
+    /// Arguments that size and place the player window
+    fn geometry_args(&self, _geometry: &Geometry) -> Vec<String> {
+        vec![]
+    }
+    /// Argument that makes the player listen for remote commands on `socket`,
+    /// `None` when the player can't be controlled
+    fn ipc_arg(&self, _socket: &Path) -> Option<OsString> {
+        None
+    }
+}
+
+pub struct Vlc;
+
+impl Player for Vlc {
+    fn name(&self) -> &str {
+        "vlc"
+    }
+
+    fn command(&self) -> Command {
+        let mut vlc = Command::new("vlc");
+        vlc.arg("-");
+        vlc
+    }
+
+    fn interpret_exit(&self, status: ExitStatus) -> PlayerExit {
+        match status.code() {
+            Some(0) | None => PlayerExit::Closed,
+            Some(_) => PlayerExit::Failed(format!("vlc exited with status: {status}")),
+        }
+    }
+
+    fn audio_only_args(&self) -> Vec<&str> {
+        vec!["--no-video"]
+    }
+
+    fn geometry_args(&self, geometry: &Geometry) -> Vec<String> {
+        // The position only applies to a window of its own
+        vec![
+            "--no-embedded-video".to_string(),
+            format!("--width={}", geometry.width),
+            format!("--height={}", geometry.height),
+            format!("--video-x={}", geometry.x),
+            format!("--video-y={}", geometry.y),
+        ]
+    }
+}
+
+pub struct Mpv;
+
+impl Player for Mpv {
+    fn name(&self) -> &str {
+        "mpv"
+    }
+
+    fn command(&self) -> Command {
+        let mut mpv = Command::new("mpv");
+        mpv.arg("--force-window=immediate").arg("-");
+        mpv
+    }
+
+    fn interpret_exit(&self, status: ExitStatus) -> PlayerExit {
+        // https://mpv.io/manual/stable/#exit-codes
+        match status.code() {
+            Some(0) | Some(4) | None => PlayerExit::Closed,
+            Some(1) => PlayerExit::Failed("mpv failed to initialize".to_string()),
+            Some(2) => PlayerExit::Failed("mpv could not play the stream".to_string()),
+            Some(_) => PlayerExit::Failed(format!("mpv exited with status: {status}")),
+        }
+    }
+
+    fn audio_only_args(&self) -> Vec<&str> {
+        // Overrides the earlier `--force-window=immediate`
+        vec!["--no-video", "--force-window=no"]
+    }
+
+    fn geometry_args(&self, geometry: &Geometry) -> Vec<String> {
+        vec![format!("--geometry={geometry}")]
+    }
+
+    fn ipc_arg(&self, socket: &Path) -> Option<OsString> {
+        let mut arg = OsString::from("--input-ipc-server=");
+        arg.push(socket);
+        Some(arg)
+    }
+}
+
+pub struct Ffplay;
+
+impl Player for Ffplay {
+    fn name(&self) -> &str {
+        "ffplay"
+    }
+
+    fn command(&self) -> Command {
+        let mut ffplay = Command::new("ffplay");
+        ffplay
+            .arg("-autoexit")
+            .arg("-loglevel")
+            .arg("error")
+            .arg("-i")
+            .arg("-");
+        ffplay
+    }
+
+    fn interpret_exit(&self, status: ExitStatus) -> PlayerExit {
+        match status.code() {
+            Some(0) | None => PlayerExit::Closed,
+            Some(_) => PlayerExit::Failed(format!("ffplay exited with status: {status}")),
+        }
+    }
+
+    fn audio_only_args(&self) -> Vec<&str> {
+        vec!["-nodisp"]
+    }
+
+    fn geometry_args(&self, geometry: &Geometry) -> Vec<String> {
+        vec![
+            "-x".to_string(),
+            geometry.width.to_string(),
+            "-y".to_string(),
+            geometry.height.to_string(),
+            "-left".to_string(),
+            geometry.x.to_string(),
+            "-top".to_string(),
+            geometry.y.to_string(),
+        ]
+    }
+}
+
+pub struct CustomPlayer {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CustomPlayer {
+    /// `template` is split like a shell would, so quoted arguments can hold
+    /// spaces. One with unbalanced quotes falls back to splitting on spaces
+    pub fn new(template: &str) -> Self {
+        let parts = shlex::split(template)
+            .unwrap_or_else(|| template.split_whitespace().map(String::from).collect());
+        let mut parts = parts.into_iter().map(|part| part.replace("{input}", "-"));
+        let program = parts.next().unwrap_or_default();
+        Self {
+            program,
+            args: parts.collect(),
+        }
+    }
+}
+
+impl Player for CustomPlayer {
+    fn name(&self) -> &str {
+        &self.program
+    }
+
+    fn command(&self) -> Command {
+        let mut custom = Command::new(&self.program);
+        custom.args(&self.args);
+        custom
+    }
+
+    fn interpret_exit(&self, status: ExitStatus) -> PlayerExit {
+        if status.success() || status.code().is_none() {
+            PlayerExit::Closed
+        } else {
+            PlayerExit::Failed(format!("{} exited with status: {status}", self.program))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_template() {
+        let player = CustomPlayer::new("celluloid --new-window {input}");
+        let command = player.command();
+        assert_eq!(command.get_program(), "celluloid");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec!["--new-window", "-"]
+        );
+
+        let player = CustomPlayer::new(r#"vlc --meta-title "Live now" '{input}'"#);
+        let command = player.command();
+        assert_eq!(command.get_program(), "vlc");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec!["--meta-title", "Live now", "-"]
+        );
+    }
+
+    #[test]
+    fn test_backend_from_config() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            player: PlayerBackend,
+        }
+
+        let mpv = toml::from_str::<Wrapper>(r#"player = "mpv""#).unwrap();
+        assert_eq!(mpv.player, PlayerBackend::Mpv);
+
+        let custom = toml::from_str::<Wrapper>(r#"player = { custom = "mplayer {input}" }"#);
+        assert_eq!(
+            custom.unwrap().player,
+            PlayerBackend::Custom("mplayer {input}".to_string())
+        );
+    }
+}
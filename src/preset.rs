@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use crate::config::PerPlatform;
+use crate::target::Platform;
+
+/// Formats tried in order until one is offered, e.g.
+/// `[presets.720p] default = ["bv*[height<=720]+ba", "b[height<=720]", "b"]`
+pub type Preset = PerPlatform<Vec<String>>;
+
+/// Presets available without configuring them, in the order the TUI cycles
+/// through them
+pub const BUILTIN: [&str; 4] = ["best", "720p", "480p-lowfps", "audio"];
+
+pub fn builtin(name: &str) -> Option<Preset> {
+    let (default, twitch): (&[&str], &[&str]) = match name {
+        "best" => (&["bv*+ba", "b"], &["best"]),
+        "720p" => (
+            &["bv*[height<=720]+ba", "b[height<=720]", "b"],
+            &["720p60", "720p", "b[height<=720]", "best"],
+        ),
+        "480p-lowfps" => (
+            &[
+                "bv*[height<=480][fps<=30]+ba",
+                "b[height<=480][fps<=30]",
+                "b[height<=480]",
+                "w",
+            ],
+            &["480p30", "480p", "360p30", "360p", "worst"],
+        ),
+        "audio" => (&["ba", "w"], &["audio_only", "ba", "worst"]),
+        _ => return None,
+    };
+    let chain = |formats: &[&str]| formats.iter().map(|format| format.to_string()).collect();
+    Some(Preset {
+        default: chain(default),
+        youtube: None,
+        twitch: Some(chain(twitch)),
+    })
+}
+
+/// Format string for `format` on the platform. A preset turns into its
+/// fallback chain joined with `/`, anything else is taken as a raw format.
+pub fn resolve(presets: &HashMap<String, Preset>, format: &str, platform: &Platform) -> String {
+    let preset = presets.get(format).cloned().or_else(|| builtin(format));
+    match preset.map(|preset| preset.get(platform).join("/")) {
+        Some(chain) if !chain.is_empty() => chain,
+        _ => format.to_string(),
+    }
+}
+
+/// Built-in presets followed by the configured ones
+pub fn names(presets: &HashMap<String, Preset>) -> Vec<String> {
+    let mut configured = presets
+        .keys()
+        .filter(|name| !BUILTIN.contains(&name.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    configured.sort();
+    BUILTIN
+        .iter()
+        .map(|name| name.to_string())
+        .chain(configured)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::{Extractor, Streamlink};
+
+    #[test]
+    fn test_resolve() {
+        let presets = toml::from_str::<HashMap<String, Preset>>(
+            r#"
+            [720p]
+            default = ["bv*[height<=720][vcodec^=avc1]+ba", "b"]
+
+            [potato]
+            default = ["w"]
+            twitch = ["160p", "worst"]
+            "#,
+        )
+        .unwrap();
+
+        #[rustfmt::skip]
+        let test_cases = vec![
+            ("best", Platform::YouTube, "bv*+ba/b"),
+            ("720p", Platform::YouTube, "bv*[height<=720][vcodec^=avc1]+ba/b"),
+            ("720p", Platform::Twitch, "bv*[height<=720][vcodec^=avc1]+ba/b"),
+            ("480p-lowfps", Platform::Twitch, "480p30/480p/360p30/360p/worst"),
+            ("potato", Platform::Twitch, "160p/worst"),
+            ("bestaudio", Platform::YouTube, "bestaudio"),
+        ];
+
+        for (format, platform, expected) in test_cases {
+            assert_eq!(
+                resolve(&presets, format, &platform),
+                expected,
+                "Failed to resolve: {format} ({platform})"
+            );
+        }
+
+        let format = resolve(&presets, "480p-lowfps", &Platform::YouTube);
+        assert_eq!(Streamlink.format(&format), "480p30,480p,480p60,worst");
+        assert_eq!(names(&presets).last().map(String::as_str), Some("potato"));
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeDelta, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::context::Context;
+use crate::utils::data_dir;
+
+/// Units YouTube grants a project per day
+pub const DAILY_QUOTA: u64 = 10_000;
+/// Estimated cost of each API call, see
+/// https://developers.google.com/youtube/v3/determine_quota_cost
+pub const SEARCH_LIST: u64 = 100;
+pub const VIDEOS_LIST: u64 = 1;
+pub const CHANNELS_LIST: u64 = 1;
+pub const LIVE_BROADCASTS_LIST: u64 = 1;
+
+/// Requests within this process go through the ledger one at a time
+static LEDGER: Mutex<()> = Mutex::new(());
+
+/// `[quota]`, keeping track of the YouTube API units spent today
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct QuotaConfig {
+    /// Units available per day, 10000 per configured API key when unset
+    pub daily_limit: Option<u64>,
+    /// Warn once this share of the limit is used
+    pub warn_percent: u64,
+    /// Units to stay under by polling less often, 0 polls as configured
+    pub budget: u64,
+    pub file: PathBuf,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            daily_limit: None,
+            warn_percent: 80,
+            budget: 0,
+            file: data_dir().join("quota.toml"),
+        }
+    }
+}
+
+impl QuotaConfig {
+    pub fn limit(&self, keys: usize) -> u64 {
+        self.daily_limit.unwrap_or(DAILY_QUOTA * keys.max(1) as u64)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct CallUsage {
+    pub calls: u64,
+    pub units: u64,
+}
+
+/// Units spent on one Pacific day, which is when YouTube resets the quota
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct Ledger {
+    pub day: Option<NaiveDate>,
+    pub used: u64,
+    #[serde(default)]
+    pub calls: BTreeMap<String, CallUsage>,
+}
+
+impl Ledger {
+    /// Today's ledger, a stale one from an earlier day starts over
+    pub fn load(file: &Path, now: DateTime<Utc>) -> Self {
+        let today = pacific_date(now);
+        fs::read_to_string(file)
+            .ok()
+            .and_then(|ledger| toml::from_str::<Ledger>(&ledger).ok())
+            .filter(|ledger| ledger.day == Some(today))
+            .unwrap_or(Ledger {
+                day: Some(today),
+                ..Default::default()
+            })
+    }
+
+    pub fn add(&mut self, call: &str, units: u64) {
+        self.used += units;
+        let usage = self.calls.entry(call.to_string()).or_default();
+        usage.calls += 1;
+        usage.units += units;
+    }
+}
+
+/// Count a request against today's quota, warning when it gets close to
+/// `limit`
+pub fn record(config: &QuotaConfig, limit: u64, call: &str, units: u64) {
+    let _guard = LEDGER.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = Ledger::load(&config.file, Utc::now());
+    let before = ledger.used;
+    ledger.add(call, units);
+
+    let warn_at = limit * config.warn_percent / 100;
+    if before < warn_at && ledger.used >= warn_at {
+        eprintln!(
+            "Used {} of {limit} YouTube API units today, consider a longer interval",
+            ledger.used
+        );
+    }
+    if let Err(e) = save(&config.file, &ledger) {
+        eprintln!("Failed to save the quota ledger: {e}");
+    }
+}
+
+/// Written next to the file and renamed over it, so a poll reading the ledger
+/// while another saves it never sees it empty
+fn save(file: &Path, ledger: &Ledger) -> io::Result<()> {
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp = file.as_os_str().to_owned();
+    temp.push(format!(".{}.tmp", std::process::id()));
+    fs::write(&temp, toml::to_string(ledger).unwrap_or_default())?;
+    fs::rename(&temp, file).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// How long to wait before polling again when a poll costs `cost` units,
+/// stretched so the rest of the budget lasts until the quota resets
+pub fn throttle(config: &QuotaConfig, interval: Duration, cost: u64) -> Duration {
+    if config.budget == 0 {
+        return interval;
+    }
+    let now = Utc::now();
+    let used = Ledger::load(&config.file, now).used;
+    let until_reset = (next_reset(now) - now).to_std().unwrap_or_default();
+    let polls_left = config.budget.saturating_sub(used) / cost.max(1);
+    match polls_left {
+        0 => until_reset.max(interval),
+        polls => interval.max(until_reset / polls as u32),
+    }
+}
+
+/// UTC offset of US Pacific time, daylight saving from the second Sunday
+/// of March to the first Sunday of November, both at 2:00 local time
+fn pacific_offset(time: DateTime<Utc>) -> FixedOffset {
+    let year = time.year();
+    let transition = |month, nth, utc_hour| {
+        NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, nth)
+            .and_then(|date| date.and_hms_opt(utc_hour, 0, 0))
+            .map(|time| time.and_utc())
+    };
+    let daylight_saving = match (transition(3, 2, 10), transition(11, 1, 9)) {
+        (Some(start), Some(end)) => start <= time && time < end,
+        _ => false,
+    };
+    let hours = if daylight_saving { -7 } else { -8 };
+    FixedOffset::east_opt(hours * 3600).expect("Valid offset")
+}
+
+pub fn pacific_date(time: DateTime<Utc>) -> NaiveDate {
+    time.with_timezone(&pacific_offset(time)).date_naive()
+}
+
+/// Next Pacific midnight, when the quota starts over
+pub fn next_reset(time: DateTime<Utc>) -> DateTime<Utc> {
+    let midnight = (pacific_date(time) + TimeDelta::days(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("Valid time")
+        .and_utc();
+    let reset = midnight - TimeDelta::seconds(pacific_offset(time).local_minus_utc() as i64);
+    // The offset can change before the next midnight
+    midnight - TimeDelta::seconds(pacific_offset(reset).local_minus_utc() as i64)
+}
+
+/// `streamdex status`, today's YouTube API usage
+pub fn status(ctx: &Context) {
+    let config = &ctx.config.quota;
+    let now = Utc::now();
+    let ledger = Ledger::load(&config.file, now);
+    let limit = config.limit(ctx.config.youtube_api_keys.len());
+    let until_reset = next_reset(now) - now;
+
+    println!(
+        "YouTube API quota for {} (Pacific), resets in {}h {}m",
+        pacific_date(now),
+        until_reset.num_hours(),
+        until_reset.num_minutes() % 60
+    );
+    println!(
+        "Used {} of {limit} units ({}%)",
+        ledger.used,
+        ledger.used * 100 / limit.max(1)
+    );
+    for (call, usage) in &ledger.calls {
+        println!(
+            "  {call:<20} {:>6} units, {} calls",
+            usage.units, usage.calls
+        );
+    }
+    if config.budget > 0 {
+        println!(
+            "Budget {} units, polling slows down to stay under it",
+            config.budget
+        );
+    }
+    if ledger.used * 100 >= limit * config.warn_percent {
+        println!("Close to the limit, requests will start failing once it's reached");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacific_reset() {
+        let time = |time: &str| time.parse::<DateTime<Utc>>().unwrap();
+        #[rustfmt::skip]
+        let test_cases = [
+            // Standard time, still the previous day in California
+            ("2024-01-15T07:59:00Z", "2024-01-14", "2024-01-15T08:00:00Z"),
+            ("2024-01-15T08:00:00Z", "2024-01-15", "2024-01-16T08:00:00Z"),
+            // Daylight saving time
+            ("2024-07-01T06:30:00Z", "2024-06-30", "2024-07-01T07:00:00Z"),
+            // Daylight saving starts after the next midnight, then before the one after
+            ("2024-03-09T12:00:00Z", "2024-03-09", "2024-03-10T08:00:00Z"),
+            ("2024-03-10T12:00:00Z", "2024-03-10", "2024-03-11T07:00:00Z"),
+            ("2024-11-02T12:00:00Z", "2024-11-02", "2024-11-03T07:00:00Z"),
+            // Daylight saving ends before the next midnight
+            ("2024-11-03T08:30:00Z", "2024-11-03", "2024-11-04T08:00:00Z"),
+        ];
+
+        for (now, date, reset) in test_cases {
+            assert_eq!(pacific_date(time(now)).to_string(), date, "Failed: {now}");
+            assert_eq!(next_reset(time(now)), time(reset), "Failed: {now}");
+        }
+    }
+
+    #[test]
+    fn test_ledger() {
+        let file =
+            std::env::temp_dir().join(format!("streamdex-test-quota-{}.toml", std::process::id()));
+        let now = "2024-05-01T20:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut ledger = Ledger::load(&file, now);
+        ledger.add("search.list", SEARCH_LIST);
+        ledger.add("search.list", SEARCH_LIST);
+        ledger.add("videos.list", VIDEOS_LIST);
+        save(&file, &ledger).unwrap();
+
+        let loaded = Ledger::load(&file, now);
+        assert_eq!(loaded.used, 201);
+        assert_eq!(loaded.calls["search.list"].calls, 2);
+        // A new Pacific day starts from zero
+        let tomorrow = now + TimeDelta::days(1);
+        assert_eq!(Ledger::load(&file, tomorrow).used, 0);
+        let _ = fs::remove_file(&file);
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use chrono::{Local, Utc};
+
+use crate::context::Context;
+use crate::runner::StreamInfo;
+
+const DEFAULT_START_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// Where to record the stream, `None` when recording is off
+pub fn record_path(ctx: &Context, info: &StreamInfo) -> Option<PathBuf> {
+    let config = &ctx.config.record;
+    (ctx.record || ctx.record_only).then(|| {
+        config
+            .directory
+            .join(render_template(&config.template, info))
+    })
+}
+
+pub fn render_template(template: &str, info: &StreamInfo) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        rendered.push_str(&rest[..open]);
+
+        let placeholder = &rest[open + 1..close];
+        let (key, start_format) = placeholder
+            .split_once(':')
+            .unwrap_or((placeholder, DEFAULT_START_FORMAT));
+        let value = match key {
+            "alias" => Some(info.alias.clone()),
+            "platform" => Some(info.platform.to_string()),
+            "id" => Some(info.video_id.clone().unwrap_or(String::from("unknown"))),
+            "title" => Some(info.title.clone().unwrap_or(String::from("untitled"))),
+            "start" => Some(
+                info.start_time
+                    .or(info.scheduled_start)
+                    .unwrap_or(Utc::now())
+                    .with_timezone(&Local)
+                    .format(start_format)
+                    .to_string(),
+            ),
+            _ => None,
+        };
+        match value {
+            Some(value) => rendered.push_str(&sanitize(&value)),
+            None => rendered.push_str(&rest[open..=close]),
+        }
+
+        rest = &rest[close + 1..];
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+/// Make a value safe to use as a single path component
+fn sanitize(value: &str) -> String {
+    let value = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+    let value = value.trim();
+    // `.` and `..` would point outside the recording directory
+    match value.chars().all(|c| c == '.') {
+        true => value.replace('.', "_"),
+        false => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone};
+
+    use super::*;
+    use crate::target::Platform;
+
+    #[test]
+    fn test_render_template() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 20, 30, 0).unwrap();
+        let info = StreamInfo {
+            alias: String::from("sora"),
+            platform: Platform::YouTube,
+            video_id: Some(String::from("dQw4w9WgXcQ")),
+            title: Some(String::from("Karaoke: 3/4 time")),
+            start_time: Some(DateTime::<Utc>::from(start)),
+            scheduled_start: None,
+        };
+
+        assert_eq!(
+            render_template("{alias}/{start:%Y%m%d}_{title}_{id}.{ext}", &info),
+            "sora/20240501_Karaoke_ 3_4 time_dQw4w9WgXcQ.{ext}"
+        );
+        assert_eq!(
+            render_template("{platform}-{start}", &info),
+            "YouTube-2024-05-01_20-30-00"
+        );
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize(".."), "__");
+        assert_eq!(sanitize(" . "), "_");
+        assert_eq!(sanitize("../etc"), ".._etc");
+        assert_eq!(sanitize("v1.2 ..."), "v1.2 ...");
+        let info = StreamInfo {
+            alias: String::from(".."),
+            platform: Platform::Twitch,
+            video_id: None,
+            title: Some(String::from(".")),
+            start_time: None,
+            scheduled_start: None,
+        };
+        assert_eq!(render_template("{alias}/{title}.ts", &info), "__/_.ts");
+    }
+}
@@ -0,0 +1,319 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use clap::ArgMatches;
+use color_eyre::eyre::{OptionExt, eyre};
+use serde_json::{Value, json};
+
+use crate::runner::StreamInfo;
+use crate::utils::parse_timestamp;
+
+/// How long to wait for the player to answer a command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sessions given a socket so far in this process
+static SESSIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Player commands sent over mpv's JSON IPC
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteCommand {
+    /// Toggle pause
+    Pause,
+    /// Toggle mute
+    Mute,
+    /// Absolute volume, or relative with a sign
+    Volume {
+        value: f64,
+        relative: bool,
+    },
+    /// Seconds to seek, absolute when not relative
+    Seek {
+        seconds: f64,
+        relative: bool,
+    },
+    Quit,
+    /// Seconds played so far
+    Position,
+    /// Length in seconds, when the player knows it
+    Duration,
+}
+
+impl RemoteCommand {
+    pub const NAMES: [&str; 7] = [
+        "pause", "mute", "volume", "seek", "quit", "position", "duration",
+    ];
+
+    /// `volume 50`, `volume +5`, `seek -30`, `seek 1:23:45`
+    pub fn parse(name: &str, value: Option<&str>) -> color_eyre::Result<Self> {
+        let value = || value.ok_or_else(|| eyre!("`{name}` needs a value"));
+        match name {
+            "pause" => Ok(RemoteCommand::Pause),
+            "mute" => Ok(RemoteCommand::Mute),
+            "quit" => Ok(RemoteCommand::Quit),
+            "position" => Ok(RemoteCommand::Position),
+            "duration" => Ok(RemoteCommand::Duration),
+            "volume" => {
+                let (value, relative) = signed(value()?, |v| v.parse::<f64>().ok())?;
+                Ok(RemoteCommand::Volume { value, relative })
+            }
+            "seek" => {
+                let (seconds, relative) =
+                    signed(value()?, |v| parse_timestamp(v).map(|s| s as f64))?;
+                Ok(RemoteCommand::Seek { seconds, relative })
+            }
+            _ => Err(eyre!("Unknown player command: {name}")),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let command = match self {
+            RemoteCommand::Pause => json!(["cycle", "pause"]),
+            RemoteCommand::Mute => json!(["cycle", "mute"]),
+            RemoteCommand::Volume {
+                value,
+                relative: true,
+            } => json!(["add", "volume", value]),
+            RemoteCommand::Volume {
+                value,
+                relative: false,
+            } => json!(["set_property", "volume", value]),
+            RemoteCommand::Seek {
+                seconds,
+                relative: true,
+            } => json!(["seek", seconds, "relative"]),
+            RemoteCommand::Seek {
+                seconds,
+                relative: false,
+            } => json!(["seek", seconds, "absolute"]),
+            RemoteCommand::Quit => json!(["quit"]),
+            RemoteCommand::Position => json!(["get_property", "time-pos"]),
+            RemoteCommand::Duration => json!(["get_property", "duration"]),
+        };
+        json!({ "command": command })
+    }
+}
+
+/// Split off a leading `+`/`-`, which makes the value relative
+fn signed(value: &str, parse: impl Fn(&str) -> Option<f64>) -> color_eyre::Result<(f64, bool)> {
+    let (sign, rest) = match value.chars().next() {
+        Some('+') => (1.0, &value[1..]),
+        Some('-') => (-1.0, &value[1..]),
+        _ => {
+            return Ok((
+                parse(value).ok_or_eyre(format!("Invalid value: {value}"))?,
+                false,
+            ));
+        }
+    };
+    let parsed = parse(rest).ok_or_eyre(format!("Invalid value: {value}"))?;
+    Ok((sign * parsed, true))
+}
+
+/// Directory holding one socket per running player
+pub fn socket_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join("streamdex"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_default();
+            std::env::temp_dir().join(format!("streamdex-{user}"))
+        }
+    }
+}
+
+/// Socket named after the stream and unique to the session, e.g.
+/// `sora-youtube-4242-1.sock`, so another session of the same stream can't
+/// take it over
+pub fn socket_path(info: &StreamInfo) -> PathBuf {
+    let session = SESSIONS.fetch_add(1, Ordering::Relaxed) + 1;
+    let name = format!("{}-{}", info.alias, info.platform)
+        .to_lowercase()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+    socket_dir().join(format!("{name}-{}-{session}.sock", std::process::id()))
+}
+
+/// Names of the sockets that currently exist
+pub fn list_sockets() -> Vec<String> {
+    let mut names = fs::read_dir(socket_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            (path.extension()? == "sock").then(|| path.file_stem()?.to_str().map(String::from))?
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// Socket matching `session` exactly, or as the only one starting with it,
+/// e.g. `sora` or `sora-youtube`
+fn find_socket(session: &str) -> color_eyre::Result<PathBuf> {
+    let sockets = list_sockets();
+    let matches = match sockets.iter().find(|name| *name == session) {
+        Some(name) => vec![name],
+        None => sockets
+            .iter()
+            .filter(|name| name.starts_with(session))
+            .collect(),
+    };
+    match matches.as_slice() {
+        [name] => Ok(socket_dir().join(format!("{name}.sock"))),
+        [] => Err(eyre!("No running player for {session}")),
+        _ => Err(eyre!(
+            "{session} matches several players: {}",
+            matches
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Send a command and wait for the player's reply
+#[cfg(unix)]
+pub async fn send(socket: &Path, command: &RemoteCommand) -> color_eyre::Result<Value> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    let stream = UnixStream::connect(socket).await?;
+    let (reader, mut writer) = stream.into_split();
+    let mut request = command.to_json().to_string();
+    request.push('\n');
+    writer.write_all(request.as_bytes()).await?;
+
+    let mut lines = BufReader::new(reader).lines();
+    tokio::time::timeout(REPLY_TIMEOUT, async {
+        // Events can arrive before the reply, the reply is the one with `error`
+        while let Some(line) = lines.next_line().await? {
+            let reply = serde_json::from_str::<Value>(&line)?;
+            if let Some(error) = reply.get("error").and_then(Value::as_str) {
+                return match error {
+                    "success" => Ok(reply.get("data").cloned().unwrap_or(Value::Null)),
+                    error => Err(eyre!("Player rejected the command: {error}")),
+                };
+            }
+        }
+        // `quit` closes the connection without replying
+        Ok(Value::Null)
+    })
+    .await
+    .map_err(|_| eyre!("The player didn't answer in time"))?
+}
+
+#[cfg(not(unix))]
+pub async fn send(_socket: &Path, _command: &RemoteCommand) -> color_eyre::Result<Value> {
+    Err(eyre!("Remote control is only supported on unix"))
+}
+
+/// `streamdex remote [SESSION] [COMMAND] [VALUE]`, lists the sessions
+/// without arguments
+pub async fn run(args: &ArgMatches) -> color_eyre::Result<()> {
+    let Some(session) = args.get_one::<String>("session") else {
+        let sockets = list_sockets();
+        if sockets.is_empty() {
+            println!("No running players with remote control");
+        }
+        for name in sockets {
+            println!("{name}");
+        }
+        return Ok(());
+    };
+
+    let name = args
+        .get_one::<String>("command")
+        .ok_or_eyre("Missing player command")?;
+    let command = RemoteCommand::parse(name, args.get_one::<String>("value").map(String::as_str))?;
+    let reply = send(&find_socket(session)?, &command).await?;
+    if !reply.is_null() {
+        println!("{reply}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        #[rustfmt::skip]
+        let test_cases = vec![
+            ("pause", None, json!(["cycle", "pause"])),
+            ("volume", Some("50"), json!(["set_property", "volume", 50.0])),
+            ("volume", Some("-5"), json!(["add", "volume", -5.0])),
+            ("seek", Some("+30"), json!(["seek", 30.0, "relative"])),
+            ("seek", Some("1:23:45"), json!(["seek", 5025.0, "absolute"])),
+            ("quit", None, json!(["quit"])),
+            ("position", None, json!(["get_property", "time-pos"])),
+            ("duration", None, json!(["get_property", "duration"])),
+        ];
+
+        for (name, value, expected) in test_cases {
+            let command = RemoteCommand::parse(name, value).unwrap();
+            assert_eq!(
+                command.to_json()["command"],
+                expected,
+                "Failed: {name} {value:?}"
+            );
+        }
+        assert!(RemoteCommand::parse("volume", None).is_err());
+        assert!(RemoteCommand::parse("seek", Some("soon")).is_err());
+    }
+
+    #[test]
+    fn test_socket_path() {
+        let info = StreamInfo {
+            alias: String::from("Sora"),
+            platform: crate::target::Platform::YouTube,
+            video_id: None,
+            title: None,
+            start_time: None,
+            scheduled_start: None,
+        };
+        let (first, second) = (socket_path(&info), socket_path(&info));
+        assert_ne!(first, second);
+        let name = first.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("sora-youtube-"), "Failed: {name}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_send() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::UnixListener;
+
+        let socket =
+            std::env::temp_dir().join(format!("streamdex-test-{}.sock", std::process::id()));
+        let _ = fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+        let player = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let request = BufReader::new(reader)
+                .lines()
+                .next_line()
+                .await
+                .unwrap()
+                .unwrap();
+            writer
+                .write_all(b"{\"event\":\"pause\"}\n{\"data\":true,\"error\":\"success\"}\n")
+                .await
+                .unwrap();
+            request
+        });
+
+        let reply = send(&socket, &RemoteCommand::Pause).await.unwrap();
+        assert_eq!(reply, json!(true));
+        assert_eq!(player.await.unwrap(), r#"{"command":["cycle","pause"]}"#);
+        let _ = fs::remove_file(&socket);
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::watch;
+
+use crate::remote::{self, RemoteCommand};
+use crate::utils::{data_dir, format_timestamp};
+
+/// How often the player is asked for its position
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Positions closer to the start aren't worth resuming from
+const MIN_POSITION: u64 = 30;
+/// Positions this close to the end count as played to the end
+const END_MARGIN: u64 = 30;
+
+/// `[resume]`, remembering where videos were stopped
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ResumeConfig {
+    pub enabled: bool,
+    /// Positions in seconds, keyed by video id
+    pub file: PathBuf,
+}
+
+impl Default for ResumeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            file: data_dir().join("positions.toml"),
+        }
+    }
+}
+
+fn read_positions(file: &Path) -> HashMap<String, u64> {
+    fs::read_to_string(file)
+        .ok()
+        .and_then(|positions| toml::from_str(&positions).ok())
+        .unwrap_or_default()
+}
+
+/// Where the video was stopped last time, if anywhere worth resuming from
+pub fn position(config: &ResumeConfig, video_id: &str) -> Option<u64> {
+    if !config.enabled {
+        return None;
+    }
+    read_positions(&config.file).get(video_id).copied()
+}
+
+/// Remember the position, or forget it when `None` or near the start
+pub fn save(file: &Path, video_id: &str, position: Option<u64>) -> io::Result<()> {
+    let mut positions = read_positions(file);
+    let changed = match position.filter(|position| *position >= MIN_POSITION) {
+        Some(position) => positions.insert(video_id.to_string(), position) != Some(position),
+        None => positions.remove(video_id).is_some(),
+    };
+    if !changed {
+        return Ok(());
+    }
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(file, toml::to_string(&positions).unwrap_or_default())
+}
+
+/// How far the player got, and how long the video is if it knows
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub position: Option<u64>,
+    pub duration: Option<u64>,
+}
+
+impl Progress {
+    /// Near the end, so there's nothing left to resume. Without a duration
+    /// it can't tell, and the position is kept.
+    pub fn finished(&self) -> bool {
+        match (self.position, self.duration) {
+            (Some(position), Some(duration)) => position + END_MARGIN >= duration,
+            _ => false,
+        }
+    }
+}
+
+/// Keep asking the player on `socket` where it is until aborted
+pub async fn track(socket: PathBuf, progress: watch::Sender<Progress>) {
+    let seconds = |reply| match reply {
+        Ok(Value::Number(seconds)) => seconds.as_f64().map(|seconds| seconds as u64),
+        _ => None,
+    };
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        // Fails until the player has created its socket
+        let Some(position) = seconds(remote::send(&socket, &RemoteCommand::Position).await) else {
+            continue;
+        };
+        // Unknown for some streams, and can show up later
+        let known = progress.borrow().duration;
+        let duration = match known {
+            Some(duration) => Some(duration),
+            None => seconds(remote::send(&socket, &RemoteCommand::Duration).await),
+        };
+        progress.send_replace(Progress {
+            position: Some(position),
+            duration,
+        });
+    }
+}
+
+/// Ask on the terminal whether to resume, without a terminal it starts over
+pub async fn ask(name: &str, position: u64) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+    print!(
+        "{name} was stopped at {}, resume from there? [Y/n] ",
+        format_timestamp(position)
+    );
+    let _ = io::stdout().flush();
+    tokio::task::spawn_blocking(|| {
+        let mut answer = String::new();
+        let _ = io::stdin().lock().read_line(&mut answer);
+        matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes")
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_position() {
+        let config = ResumeConfig {
+            enabled: true,
+            file: std::env::temp_dir().join(format!(
+                "streamdex-test-positions-{}.toml",
+                std::process::id()
+            )),
+        };
+        let _ = fs::remove_file(&config.file);
+
+        save(&config.file, "abc", Some(754)).unwrap();
+        save(&config.file, "def", Some(10)).unwrap();
+        assert_eq!(position(&config, "abc"), Some(754));
+        assert_eq!(position(&config, "def"), None);
+
+        save(&config.file, "abc", None).unwrap();
+        assert_eq!(position(&config, "abc"), None);
+        let _ = fs::remove_file(&config.file);
+    }
+
+    #[test]
+    fn test_progress_finished() {
+        let progress = |position, duration| Progress { position, duration };
+        assert!(progress(Some(3590), Some(3600)).finished());
+        assert!(!progress(Some(1200), Some(3600)).finished());
+        // The extractor can be done long before the viewer, only the player knows
+        assert!(!progress(Some(1200), None).finished());
+        assert!(!progress(None, Some(3600)).finished());
+    }
+}
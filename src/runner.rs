@@ -1,125 +1,762 @@
-use std::process::{Command, Stdio};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-pub fn watch_with_ytdlp_and_vlc(
-    url: String,
-    res: String,
-    range: Option<String>,
-    print_command: bool,
-) -> color_eyre::Result<()> {
-    if !print_command {
-        println!("Running with yt-dlp + vlc");
-    }
-    // #[allow(clippy::zombie_processes)]
-    let mut ytdlp = Command::new("yt-dlp");
-    ytdlp
-        .arg(url)
-        .arg("-f")
-        .arg(res)
-        .arg("-q")
-        .arg("-4")
-        .arg("--cookies-from-browser")
-        .arg("firefox")
-        .arg("--mark-watched")
-        .arg("--wait-for-video")
-        .arg("5")
-        // .args(match wait_for_video {
-        //     true => vec!["--wait-for-video".into(), "5"],
-        //     false => vec![],
-        // })
-        // .args(match wait_for_video {
-        //     WaitForVideo::Wait(range) => vec!["--wait-for-video".into(), range.clone()],
-        //     WaitForVideo::NoWait => vec!["--no-wait-for-video".into()],
-        // })
-        .arg("--downloader")
-        .arg("ffmpeg")
-        .args(match range {
-            Some(range) => vec!["--download-sections".to_owned(), range],
-            None => vec![],
-        })
-        .arg("-o")
-        .arg("-")
-        .stdout(Stdio::piped());
-    // .spawn()
-    // .unwrap();
-    let mut vlc = Command::new("vlc");
-    let vlc = vlc.arg("-").stdout(Stdio::piped());
-    if print_command {
-        println!(
-            "yt-dlp {} | vlc {}",
-            ytdlp
-                .get_args()
-                .map(|arg| arg.to_str())
-                .collect::<Option<Vec<&str>>>()
-                .ok_or_else(|| color_eyre::eyre::eyre!("Failed to convert yt-dlp args to string"))?
-                .join(" ")
-                .to_owned(),
-            vlc.get_args()
-                .map(|arg| arg.to_str())
-                .collect::<Option<Vec<&str>>>()
-                .ok_or_else(|| color_eyre::eyre::eyre!("Failed to convert vlc args to string"))?
-                .join(" ")
-                .to_owned(),
-        );
-        Ok(())
-    } else {
-        let mut ytdlp = ytdlp.spawn()?;
-        let mut vlc = vlc
-            .stdin(Stdio::from(ytdlp.stdout.take().ok_or_else(|| {
-                color_eyre::eyre::eyre!("Failed to take yt-dlp stdout")
-            })?))
-            .spawn()?;
+use chrono::{DateTime, Utc};
+use color_eyre::Section;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 
-        let running = Arc::new(AtomicBool::new(true));
-        let r = running.clone();
+use crate::config::ReconnectConfig;
+use crate::context::Context;
+use crate::extractor::{Extractor, TimeRange};
+use crate::hooks::{self, Hook};
+use crate::multiview::Geometry;
+use crate::plan::LaunchPlan;
+use crate::player::{Player, PlayerExit};
+use crate::record::record_path;
+use crate::remote::socket_path;
+use crate::resume::{self, Progress};
+use crate::supervisor::Session;
+use crate::target::Platform;
 
-        ctrlc::set_handler(move || {
-            println!("\nReceived Ctrl+C, cleaning up...");
-            r.store(false, Ordering::SeqCst);
-        })?;
+/// What is known about the stream being watched
+#[derive(Debug, Clone)]
+pub struct StreamInfo {
+    /// Alias name, or the channel handle/username when not watching an alias
+    pub alias: String,
+    pub platform: Platform,
+    pub video_id: Option<String>,
+    pub title: Option<String>,
+    /// When the broadcast actually started
+    pub start_time: Option<DateTime<Utc>>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+}
 
-        loop {
-            std::thread::sleep(Duration::from_secs(1));
+impl Display for StreamInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.alias, self.platform)
+    }
+}
 
-            if !running.load(Ordering::SeqCst) {
-                let _ = ytdlp.kill();
-                let _ = ytdlp.wait();
-                break;
-            }
+/// A session that ran at least this long resets the reconnect attempts
+const STABLE_SESSION: Duration = Duration::from_secs(60);
+/// Chunks buffered between the extractor and the player, about 4 MiB
+const CHANNEL_CHUNKS: usize = 64;
+/// Stall restarts in a row before handing over to the reconnect logic
+const MAX_STALL_RESTARTS: u32 = 3;
+/// Lines of extractor stderr kept to tell why it failed
+const STDERR_TAIL: usize = 20;
 
-            if let Ok(Some(status)) = vlc.try_wait() {
-                if let Some(mut out) = vlc.stdout.take() {
-                    let mut buffer = String::new();
-                    use std::io::Read;
-                    out.read_to_string(&mut buffer)?;
-                    println!("VLC output: {}", buffer);
-                }
-                println!("VLC exited with status: {}", status);
+#[derive(Debug, PartialEq)]
+pub enum WatchEnd {
+    /// Player closed, Ctrl+C pressed, or only printing the command
+    Stopped,
+    /// The extractor exited by itself, the stream might have dropped
+    ExtractorExited,
+}
+
+/// Everything needed to launch one stream
+pub struct Pipeline {
+    pub url: String,
+    pub format: String,
+    pub range: Option<TimeRange>,
+    pub live_from_start: bool,
+    pub audio_only: bool,
+    pub geometry: Option<Geometry>,
+    pub extractor: Box<dyn Extractor>,
+    /// `None` records without a player
+    pub player: Option<Box<dyn Player>>,
+    /// Socket the player listens on for remote commands
+    pub ipc_socket: Option<PathBuf>,
+    /// Positions file and the video id the playback position is saved
+    /// under, needs `ipc_socket`
+    pub resume: Option<(PathBuf, String)>,
+    pub record: Option<PathBuf>,
+    /// Restart the extractor when no data arrives for this long. Only for
+    /// live streams, a restarted video would play again from its start
+    pub stall_timeout: Option<Duration>,
+    pub throughput: Arc<Throughput>,
+}
+
+impl Pipeline {
+    pub fn new(ctx: &Context, platform: Platform, url: String, info: &StreamInfo) -> Self {
+        let default_format = match (ctx.audio_only, platform) {
+            (true, _) => "audio",
+            (false, Platform::Twitch) => &ctx.config.default_parameters.format.twitch,
+            (false, _) => &ctx.config.default_parameters.format.youtube,
+        };
+        let stall_timeout = ctx.config.reconnect.stall_timeout;
+        let player = (!ctx.record_only).then(|| ctx.config.player(&platform));
+        let ipc_socket = player.as_ref().and_then(|player| {
+            let socket = socket_path(info);
+            player.ipc_arg(&socket).map(|_| socket)
+        });
+        let resume = match ctx.vod && ctx.config.resume.enabled && ipc_socket.is_some() {
+            true => info
+                .video_id
+                .clone()
+                .map(|video_id| (ctx.config.resume.file.clone(), video_id)),
+            false => None,
+        };
+        Self {
+            url,
+            format: ctx
+                .config
+                .format(ctx.format.as_deref().unwrap_or(default_format), &platform),
+            range: ctx.range,
+            live_from_start: ctx.live_from_start,
+            audio_only: ctx.audio_only,
+            geometry: ctx.geometry,
+            extractor: ctx.config.extractor(&platform),
+            player,
+            ipc_socket,
+            resume,
+            record: record_path(ctx, info),
+            stall_timeout: (stall_timeout > 0 && !ctx.vod)
+                .then(|| Duration::from_secs(stall_timeout)),
+            throughput: Arc::new(Throughput::default()),
+        }
+    }
+
+    /// Pipe the extractor into the player and/or the recording. Without a
+    /// player the stream is only recorded until the extractor reaches its end.
+    pub async fn watch(&self, session: &Session) -> color_eyre::Result<WatchEnd> {
+        let extractor = self.extractor.as_ref();
+        let player = self.player.as_deref();
+        let player_command = self.player_command();
 
-                let _ = ytdlp.kill();
-                let _ = ytdlp.wait();
+        match player {
+            Some(player) => session.log(format!(
+                "Running with {} + {}",
+                extractor.name(),
+                player.name()
+            )),
+            None => session.log(format!("Recording with {}", extractor.name())),
+        }
 
-                break;
+        let file = match &self.record {
+            Some(record) => {
+                if let Some(parent) = record.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                session.log(format!("Recording to {}", record.display()));
+                // Appending keeps what was recorded before a reconnect
+                Some(
+                    File::options()
+                        .create(true)
+                        .append(true)
+                        .open(record)
+                        .await?,
+                )
             }
+            None => None,
+        };
+        if player.is_none() && file.is_none() {
+            return Err(color_eyre::eyre::eyre!(
+                "Nothing to do without a player or a recording"
+            ));
+        }
+        if let Some(socket) = &self.ipc_socket {
+            if let Some(parent) = socket.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            // Left behind by a player that didn't exit cleanly
+            let _ = fs::remove_file(socket).await;
+        }
 
-            if let Ok(Some(status)) = ytdlp.try_wait() {
-                if let Some(mut out) = ytdlp.stdout.take() {
+        let mut player_process = match player_command {
+            Some(player_command) => Some(
+                tokio::process::Command::from(player_command)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(session.stderr())
+                    .kill_on_drop(true)
+                    .spawn()?,
+            ),
+            None => None,
+        };
+        let player_stdin = match player_process.as_mut() {
+            Some(player_process) => Some(
+                player_process
+                    .stdin
+                    .take()
+                    .ok_or_else(|| color_eyre::eyre::eyre!("Failed to take player stdin"))?,
+            ),
+            None => None,
+        };
+        // Read while playing, a chatty player would block on a full pipe otherwise
+        let player_output = player_process
+            .as_mut()
+            .and_then(|player_process| player_process.stdout.take())
+            .map(|mut stdout| {
+                tokio::spawn(async move {
                     let mut buffer = String::new();
-                    use std::io::Read;
-                    out.read_to_string(&mut buffer)?;
-                    println!("yt-dlp output: {}", buffer);
+                    let _ = stdout.read_to_string(&mut buffer).await;
+                    buffer
+                })
+            });
+
+        let tracker = match (&self.resume, &self.ipc_socket) {
+            (Some(_), Some(socket)) => {
+                let (progress, receiver) = watch::channel(Progress::default());
+                Some((
+                    tokio::spawn(resume::track(socket.clone(), progress)),
+                    receiver,
+                ))
+            }
+            _ => None,
+        };
+
+        let (sender, receiver) = mpsc::channel(CHANNEL_CHUNKS);
+        let mut sink = tokio::spawn(sink(receiver, player_stdin, file));
+        let mut extractor_process = self.spawn_extractor(session, sender.clone())?;
+        let mut failure = None;
+        let mut extractor_succeeded = false;
+
+        let mut stall_restarts = 0;
+        let mut restarted_at = Instant::now();
+        let mut last_sample = (Instant::now(), self.throughput.total_bytes());
+        let mut tick = tokio::time::interval(Duration::from_secs(1));
+
+        let end = loop {
+            tokio::select! {
+                _ = session.cancelled() => {
+                    stop_process(&mut extractor_process.child).await;
+                    break WatchEnd::Stopped;
+                }
+                status = wait_for(player_process.as_mut()) => {
+                    if let Some(player) = player {
+                        if let Some(output) = player_output {
+                            let output = output.await.unwrap_or_default();
+                            session.log(format!("{} output: {}", player.name(), output));
+                        }
+                        match status.map(|status| player.interpret_exit(status)) {
+                            Ok(PlayerExit::Closed) => session.log(format!("{} closed", player.name())),
+                            Ok(PlayerExit::Failed(reason)) => session.log(reason),
+                            Err(e) => session.log(format!("Failed to wait for {}: {e}", player.name())),
+                        }
+                    }
+
+                    stop_process(&mut extractor_process.child).await;
+
+                    break WatchEnd::Stopped;
                 }
-                println!("yt-dlp exited with status: {}", status);
+                status = extractor_process.child.wait() => {
+                    match status {
+                        Ok(status) => {
+                            session.log(format!("{} exited with status: {}", extractor.name(), status));
+                            extractor_succeeded = status.success();
+                            if !status.success() {
+                                let stderr = extractor_process.stderr_tail().await;
+                                failure = extractor.classify(&self.url, &self.format, &stderr);
+                            }
+                        }
+                        Err(e) => session.log(format!("Failed to wait for {}: {e}", extractor.name())),
+                    }
+                    break WatchEnd::ExtractorExited;
+                }
+                _ = tick.tick() => {
+                    last_sample = self.throughput.sample(last_sample);
+
+                    if let Some(stall_timeout) = self.stall_timeout
+                        && !self.throughput.backpressured.load(Ordering::Relaxed)
+                        && self
+                            .throughput
+                            .idle()
+                            .is_some_and(|idle| idle >= stall_timeout)
+                    {
+                        stop_process(&mut extractor_process.child).await;
 
-                let _ = vlc.kill();
-                let _ = vlc.wait();
+                        stall_restarts += 1;
+                        if stall_restarts > MAX_STALL_RESTARTS {
+                            session.log("Stream keeps stalling, giving up on this session");
+                            break WatchEnd::ExtractorExited;
+                        }
+                        session.log(format!(
+                            "No data for {} seconds, restarting {}",
+                            stall_timeout.as_secs(),
+                            extractor.name()
+                        ));
+                        extractor_process = self.spawn_extractor(session, sender.clone())?;
+                        restarted_at = Instant::now();
+                    } else if let Some(stall_timeout) = self.stall_timeout
+                        && restarted_at.elapsed() >= stall_timeout * 2
+                    {
+                        stall_restarts = 0;
+                    }
+                }
+            }
+        };
 
-                break;
+        // Closing the channel lets the sink flush the recording and finish
+        drop(sender);
+        let mut sink_finished = false;
+        let mut played_out = false;
+        if end == WatchEnd::ExtractorExited && extractor_succeeded && self.resume.is_some() {
+            // The whole video was sent, the player ends it by itself once it
+            // has played the rest
+            tokio::select! {
+                _ = wait_for(player_process.as_mut()) => played_out = true,
+                _ = session.cancelled() => (),
             }
+        } else if end == WatchEnd::ExtractorExited {
+            // Let the player catch up with what was already received
+            tokio::select! {
+                _ = &mut sink => sink_finished = true,
+                _ = wait_for(player_process.as_mut()) => (),
+                _ = session.cancelled() => (),
+            }
+        }
+        if let Some(player_process) = player_process.as_mut() {
+            stop_process(player_process).await;
+        }
+        if let Some((tracker, progress)) = tracker
+            && let Some((file, video_id)) = &self.resume
+        {
+            tracker.abort();
+            let progress = *progress.borrow();
+            // Without a duration the player playing out by itself is the
+            // only sign it reached the end
+            let finished = progress.finished() || (played_out && progress.duration.is_none());
+            let position = match finished {
+                true => None,
+                false => progress.position.map(|position| {
+                    self.range.and_then(|range| range.from).unwrap_or(0) + position
+                }),
+            };
+            if let Err(e) = resume::save(file, video_id, position) {
+                session.log(format!("Failed to save the playback position: {e}"));
+            }
+        }
+        if let Some(socket) = &self.ipc_socket {
+            let _ = fs::remove_file(socket).await;
+        }
+        if !sink_finished {
+            let _ = sink.await;
+        }
+
+        session.log(format!("Received {}", self.throughput));
+        if let Some(error) = failure {
+            // Relaunching won't get past these, so they end the session
+            let hint = error.hint().unwrap_or_default();
+            return Err(error).suggestion(hint);
+        }
+        if player.is_none() && end == WatchEnd::ExtractorExited {
+            session.log("Recording finished");
+        }
+
+        Ok(end)
+    }
+
+    pub fn player_command(&self) -> Option<Command> {
+        let player = self.player.as_ref()?;
+        let mut command = player.command();
+        if self.audio_only {
+            command.args(player.audio_only_args());
+        } else if let Some(geometry) = &self.geometry {
+            command.args(player.geometry_args(geometry));
+        }
+        if let Some(arg) = self
+            .ipc_socket
+            .as_ref()
+            .and_then(|socket| player.ipc_arg(socket))
+        {
+            command.arg(arg);
+        }
+        Some(command)
+    }
+
+    pub fn extractor_command(&self) -> Command {
+        let mut command = self.extractor.command(
+            &self.url,
+            &self.format,
+            self.range.as_ref(),
+            self.live_from_start,
+        );
+        command.stdout(Stdio::piped());
+        // Own process group, so stopping it also stops the ffmpeg it spawns
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        command
+    }
+
+    /// Start the extractor with a task forwarding its output to the sink
+    /// and another one relaying its errors
+    fn spawn_extractor(
+        &self,
+        session: &Session,
+        sender: Sender<Vec<u8>>,
+    ) -> color_eyre::Result<ExtractorProcess> {
+        let mut child = tokio::process::Command::from(self.extractor_command())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdout = child.stdout.take().ok_or_else(|| {
+            color_eyre::eyre::eyre!("Failed to take {} stdout", self.extractor.name())
+        })?;
+        let stderr = child.stderr.take().ok_or_else(|| {
+            color_eyre::eyre::eyre!("Failed to take {} stderr", self.extractor.name())
+        })?;
+        // An extractor that never sends anything has stalled too
+        self.throughput.touch();
+        tokio::spawn(forward(stdout, sender, self.throughput.clone()));
+        let stderr = tokio::spawn(relay_stderr(stderr, session.is_quiet()));
+        Ok(ExtractorProcess { child, stderr })
+    }
+}
+
+struct ExtractorProcess {
+    child: Child,
+    /// Resolves to the last lines of stderr once the extractor closes it
+    stderr: JoinHandle<String>,
+}
+
+impl ExtractorProcess {
+    async fn stderr_tail(&mut self) -> String {
+        // ffmpeg spawned by the extractor can hold the pipe open a bit longer
+        match tokio::time::timeout(Duration::from_secs(1), &mut self.stderr).await {
+            Ok(Ok(tail)) => tail,
+            _ => String::new(),
+        }
+    }
+}
+
+/// Amount of data flowing through the pipe, updated while watching
+#[derive(Debug, Default)]
+pub struct Throughput {
+    total: AtomicU64,
+    rate: AtomicU64,
+    last_data: Mutex<Option<Instant>>,
+    /// The player isn't reading (paused or slow), so no data isn't a stall
+    backpressured: AtomicBool,
+}
+
+impl Display for Throughput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} MiB ({} KiB/s)",
+            self.total_bytes() as f64 / (1024.0 * 1024.0),
+            self.bytes_per_second() / 1024
+        )
+    }
+}
+
+impl Throughput {
+    pub fn total_bytes(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Rate over the last sampling period
+    pub fn bytes_per_second(&self) -> u64 {
+        self.rate.load(Ordering::Relaxed)
+    }
+
+    /// Time since data last arrived or the extractor was started, `None`
+    /// before that
+    pub fn idle(&self) -> Option<Duration> {
+        self.last_data
+            .lock()
+            .ok()
+            .and_then(|last_data| last_data.map(|instant| instant.elapsed()))
+    }
+
+    fn add(&self, bytes: usize) {
+        self.total.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.touch();
+    }
+
+    fn touch(&self) {
+        if let Ok(mut last_data) = self.last_data.lock() {
+            *last_data = Some(Instant::now());
+        }
+    }
+
+    fn sample(&self, (at, total): (Instant, u64)) -> (Instant, u64) {
+        let now = Instant::now();
+        let current = self.total_bytes();
+        let elapsed = now.duration_since(at).as_secs_f64();
+        if elapsed > 0.0 {
+            let rate = (current.saturating_sub(total)) as f64 / elapsed;
+            self.rate.store(rate as u64, Ordering::Relaxed);
+        }
+        (now, current)
+    }
+}
+
+/// Watch the stream in its own session, reconnecting while `still_live`
+/// confirms it. With `--dry-run` or `--print-command` only the plan is printed.
+pub async fn launch<L, Fut>(
+    ctx: &Context,
+    url: String,
+    info: &StreamInfo,
+    still_live: L,
+) -> color_eyre::Result<()>
+where
+    L: FnMut() -> Fut,
+    Fut: Future<Output = color_eyre::Result<bool>>,
+{
+    let pipeline = Pipeline::new(ctx, info.platform, url, info);
+    if ctx.print_command || ctx.dry_run.is_some() {
+        return LaunchPlan::new(ctx, &pipeline, info).print(ctx.dry_run);
+    }
+
+    let label = match pipeline.audio_only {
+        true => format!("{info} [audio]"),
+        false => info.to_string(),
+    };
+    let session = ctx.supervisor.start(
+        label,
+        pipeline.throughput.clone(),
+        pipeline.ipc_socket.clone(),
+    );
+    if ctx.vod
+        && ctx.config.resume.enabled
+        && info.video_id.is_some()
+        && pipeline.resume.is_none()
+        && let Some(player) = &pipeline.player
+    {
+        session.log(format!(
+            "{} can't report its position, where this video is stopped won't be remembered",
+            player.name()
+        ));
+    }
+    hooks::run(ctx, Hook::PlayStart, info, &pipeline.url);
+    let result = watch_with_reconnect(
+        &ctx.config.reconnect,
+        &session,
+        || pipeline.watch(&session),
+        still_live,
+    )
+    .await;
+    hooks::run(ctx, Hook::PlayEnd, info, &pipeline.url);
+    result
+}
+
+/// Keep relaunching `watch` while the extractor drops out and `still_live`
+/// confirms the broadcast is still going, up to the configured retries
+pub async fn watch_with_reconnect<W, WFut, L, LFut>(
+    config: &ReconnectConfig,
+    session: &Session,
+    mut watch: W,
+    mut still_live: L,
+) -> color_eyre::Result<()>
+where
+    W: FnMut() -> WFut,
+    WFut: Future<Output = color_eyre::Result<WatchEnd>>,
+    L: FnMut() -> LFut,
+    LFut: Future<Output = color_eyre::Result<bool>>,
+{
+    let mut attempt = 0;
+    loop {
+        let started = Instant::now();
+        if watch().await? == WatchEnd::Stopped || session.is_cancelled() {
+            return Ok(());
+        }
+        if started.elapsed() >= STABLE_SESSION {
+            attempt = 0;
         }
+        if attempt >= config.retries {
+            session.log(format!("Giving up after {attempt} reconnect attempts"));
+            return Ok(());
+        }
+
+        let delay = config
+            .backoff
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(config.max_backoff);
+        session.log(format!(
+            "Stream dropped, checking again in {delay} seconds..."
+        ));
+        if !session.sleep(Duration::from_secs(delay)).await {
+            return Ok(());
+        }
+
+        if !still_live().await? {
+            session.log("Stream has ended");
+            return Ok(());
+        }
+        attempt += 1;
+        session.log(format!(
+            "Still live, reconnecting ({attempt}/{})",
+            config.retries
+        ));
+    }
+}
+
+/// Wait for the process to exit, forever when there is none
+async fn wait_for(process: Option<&mut Child>) -> std::io::Result<ExitStatus> {
+    match process {
+        Some(process) => process.wait().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Ask the process (and its process group) to terminate, killing it if it
+/// doesn't exit in time
+async fn stop_process(process: &mut Child) {
+    if let Ok(Some(_)) = process.try_wait() {
+        return;
+    }
+    #[cfg(unix)]
+    if let Some(id) = process.id() {
+        unsafe {
+            libc::kill(-(id as i32), libc::SIGTERM);
+            libc::kill(id as i32, libc::SIGTERM);
+        }
+    }
+    if tokio::time::timeout(Duration::from_secs(3), process.wait())
+        .await
+        .is_err()
+    {
+        let _ = process.kill().await;
+    }
+}
+
+/// Read the extractor output into the sink channel until EOF
+async fn forward(
+    mut extractor_stdout: ChildStdout,
+    sender: Sender<Vec<u8>>,
+    throughput: Arc<Throughput>,
+) {
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = match extractor_stdout.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        throughput.add(read);
+        throughput.backpressured.store(true, Ordering::Relaxed);
+        let sent = sender.send(buffer[..read].to_vec()).await;
+        throughput.backpressured.store(false, Ordering::Relaxed);
+        throughput.touch();
+        if sent.is_err() {
+            break;
+        }
+    }
+}
+
+/// Pass the extractor errors on to the terminal, keeping the last lines
+async fn relay_stderr(stderr: ChildStderr, quiet: bool) -> String {
+    let mut lines = BufReader::new(stderr).lines();
+    let mut tail = VecDeque::with_capacity(STDERR_TAIL);
+    while let Ok(Some(line)) = lines.next_line().await {
+        if !quiet {
+            eprintln!("{line}");
+        }
+        if tail.len() == STDERR_TAIL {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+    Vec::from(tail).join("\n")
+}
+
+/// Write the stream into the player and the recording until the extractor
+/// output ends. Either one failing doesn't stop the other.
+async fn sink(
+    mut receiver: Receiver<Vec<u8>>,
+    mut player_stdin: Option<ChildStdin>,
+    mut file: Option<File>,
+) {
+    while let Some(chunk) = receiver.recv().await {
+        if let Some(recording) = file.as_mut()
+            && let Err(e) = recording.write_all(&chunk).await
+        {
+            eprintln!("Recording stopped: {e}");
+            file = None;
+        }
+        if let Some(stdin) = player_stdin.as_mut()
+            && stdin.write_all(&chunk).await.is_err()
+        {
+            player_stdin = None;
+        }
+        if player_stdin.is_none() && file.is_none() {
+            break;
+        }
+    }
+    if let Some(mut recording) = file {
+        let _ = recording.flush().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervisor::Supervisor;
+
+    fn no_backoff(retries: u32) -> ReconnectConfig {
+        ReconnectConfig {
+            retries,
+            backoff: 0,
+            max_backoff: 0,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_until_offline() {
+        let session = Supervisor::default().start(String::from("test"), Arc::default(), None);
+        let mut launches = 0;
+        let mut checks = 0;
+        watch_with_reconnect(
+            &no_backoff(5),
+            &session,
+            || {
+                launches += 1;
+                async { Ok(WatchEnd::ExtractorExited) }
+            },
+            || {
+                checks += 1;
+                let still_live = checks < 3;
+                async move { Ok(still_live) }
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!((launches, checks), (3, 3));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_gives_up() {
+        let session = Supervisor::default().start(String::from("test"), Arc::default(), None);
+        let mut launches = 0;
+        watch_with_reconnect(
+            &no_backoff(2),
+            &session,
+            || {
+                launches += 1;
+                async { Ok(WatchEnd::ExtractorExited) }
+            },
+            || async { Ok(true) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(launches, 3);
+    }
+
+    #[test]
+    fn test_throughput() {
+        let throughput = Throughput::default();
+        assert_eq!(throughput.idle(), None);
+        // Started without any data yet, which already counts as idle
+        throughput.touch();
+        assert!(throughput.idle().is_some());
 
-        Ok(())
+        let start = (Instant::now() - Duration::from_secs(2), 0);
+        throughput.add(4096);
+        throughput.sample(start);
+        assert_eq!(throughput.total_bytes(), 4096);
+        assert!((1900..=2048).contains(&throughput.bytes_per_second()));
+        assert!(throughput.idle().is_some());
     }
 }
@@ -0,0 +1,246 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio_util::sync::{CancellationToken, WaitForCancellationFuture};
+
+use crate::runner::Throughput;
+
+/// Keeps track of the running sessions and cancels them, together with every
+/// wait loop, on SIGINT/SIGTERM. Cheap to clone, all clones share the state.
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    shutdown: CancellationToken,
+    signals_installed: AtomicBool,
+    /// Sessions must not write to the terminal, e.g. while the TUI owns it
+    quiet: AtomicBool,
+    next_id: AtomicU64,
+    sessions: Mutex<Vec<SessionStatus>>,
+}
+
+/// A running session as seen from outside of it
+#[derive(Clone)]
+pub struct SessionStatus {
+    pub id: u64,
+    pub label: String,
+    pub throughput: Arc<Throughput>,
+    /// Socket for remote commands, when the player supports them
+    pub ipc_socket: Option<PathBuf>,
+    cancel: CancellationToken,
+}
+
+impl Supervisor {
+    /// Listen for SIGINT/SIGTERM, only the first call installs the listener.
+    /// The first signal cancels everything, a second one exits right away.
+    pub fn handle_signals(&self) -> std::io::Result<()> {
+        if self.inner.signals_installed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let mut signals = Signals::new()?;
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            let signal = signals.recv().await;
+            let running = supervisor.sessions();
+            match running.len() {
+                0 => println!("\nReceived {signal}, cleaning up..."),
+                count => println!("\nReceived {signal}, stopping {count} session(s)..."),
+            }
+            supervisor.shutdown();
+
+            let signal = signals.recv().await;
+            eprintln!("Received {signal} again, exiting without cleaning up");
+            std::process::exit(130);
+        });
+        Ok(())
+    }
+
+    /// Stop sessions and their processes from writing to the terminal
+    pub fn set_quiet(&self) {
+        self.inner.quiet.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.inner.quiet.load(Ordering::Relaxed)
+    }
+
+    pub fn shutdown(&self) {
+        self.inner.shutdown.cancel();
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.inner.shutdown.is_cancelled()
+    }
+
+    /// Sleep unless shutting down, returns `false` when cancelled
+    pub async fn sleep(&self, duration: Duration) -> bool {
+        sleep_unless_cancelled(&self.inner.shutdown, duration).await
+    }
+
+    /// Register a new session, it's removed again when the returned handle is dropped
+    pub fn start(
+        &self,
+        label: String,
+        throughput: Arc<Throughput>,
+        ipc_socket: Option<PathBuf>,
+    ) -> Session {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let cancel = self.inner.shutdown.child_token();
+        if let Ok(mut sessions) = self.inner.sessions.lock() {
+            sessions.push(SessionStatus {
+                id,
+                label,
+                throughput,
+                ipc_socket,
+                cancel: cancel.clone(),
+            });
+        }
+        Session {
+            id,
+            cancel,
+            supervisor: self.clone(),
+        }
+    }
+
+    pub fn sessions(&self) -> Vec<SessionStatus> {
+        self.inner
+            .sessions
+            .lock()
+            .map(|sessions| sessions.clone())
+            .unwrap_or_default()
+    }
+
+    /// Wait until every session has ended, or the timeout passes
+    pub async fn wait_for_sessions(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, async {
+            while !self.sessions().is_empty() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await;
+    }
+
+    /// Stop a single session, leaving the others running
+    pub fn stop(&self, id: u64) -> bool {
+        self.sessions()
+            .iter()
+            .find(|session| session.id == id)
+            .map(|session| session.cancel.cancel())
+            .is_some()
+    }
+}
+
+/// Handle held by whoever runs the session
+pub struct Session {
+    pub id: u64,
+    cancel: CancellationToken,
+    supervisor: Supervisor,
+}
+
+impl Session {
+    pub fn cancelled(&self) -> WaitForCancellationFuture<'_> {
+        self.cancel.cancelled()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Sleep unless the session is stopped, returns `false` when cancelled
+    pub async fn sleep(&self, duration: Duration) -> bool {
+        sleep_unless_cancelled(&self.cancel, duration).await
+    }
+
+    pub fn log(&self, message: impl Display) {
+        if !self.is_quiet() {
+            println!("{message}");
+        }
+    }
+
+    /// Where the session's child processes write their errors
+    pub fn stderr(&self) -> Stdio {
+        match self.is_quiet() {
+            true => Stdio::null(),
+            false => Stdio::inherit(),
+        }
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.supervisor.is_quiet()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Ok(mut sessions) = self.supervisor.inner.sessions.lock() {
+            sessions.retain(|session| session.id != self.id);
+        }
+    }
+}
+
+async fn sleep_unless_cancelled(token: &CancellationToken, duration: Duration) -> bool {
+    tokio::select! {
+        _ = token.cancelled() => false,
+        _ = tokio::time::sleep(duration) => true,
+    }
+}
+
+struct Signals {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    async fn recv(&mut self) -> &'static str {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => "Ctrl+C",
+            _ = self.terminate.recv() => "SIGTERM",
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+            "Ctrl+C"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sessions() {
+        let supervisor = Supervisor::default();
+        let first = supervisor.start(String::from("first"), Arc::default(), None);
+        let second = supervisor.start(String::from("second"), Arc::default(), None);
+        assert_eq!(supervisor.sessions().len(), 2);
+
+        assert!(supervisor.stop(first.id));
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert!(!first.sleep(Duration::from_secs(60)).await);
+
+        drop(first);
+        assert_eq!(supervisor.sessions().len(), 1);
+
+        supervisor.shutdown();
+        second.cancelled().await;
+        assert!(!supervisor.sleep(Duration::from_secs(60)).await);
+    }
+}
@@ -6,10 +6,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize};
 use url::Url;
 
-use crate::{
-    context::Context, error::Error, twitch::get_twitch_username,
-    youtube::get_youtube_channel_handle,
-};
+use crate::{context::Context, error::Error, twitch::get_twitch_username, youtube::YouTubeChannel};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Links {
@@ -17,6 +14,14 @@ pub struct Links {
     pub youtube: Option<LinkItem>,
     #[serde(default, deserialize_with = "deserialize_optional_link")]
     pub twitch: Option<LinkItem>,
+    /// Record this target headlessly instead of watching it
+    #[serde(default)]
+    pub record_only: bool,
+    /// Format or preset used unless `-f` is given
+    pub format: Option<String>,
+    /// Listen without a video window
+    #[serde(default)]
+    pub audio_only: bool,
 }
 
 impl Display for Links {
@@ -27,7 +32,11 @@ impl Display for Links {
             (None, Some(twitch)) => &twitch.display,
             _ => "",
         };
-        write!(f, "{something}")
+        write!(f, "{something}")?;
+        if self.audio_only {
+            write!(f, " (audio)")?;
+        }
+        Ok(())
     }
 }
 
@@ -36,6 +45,8 @@ pub struct LinkItem {
     pub url: Url,
     pub username: String,
     pub display: String,
+    /// Channel of a YouTube link
+    pub channel: Option<YouTubeChannel>,
 }
 
 fn deserialize_optional_link<'de, D>(deserializer: D) -> Result<Option<LinkItem>, D::Error>
@@ -57,18 +68,18 @@ fn parse_link_item(s: &str) -> Result<LinkItem, Box<dyn std::error::Error>> {
     let url = Url::parse(s)?;
     let platform = detect_platform(&url);
 
-    let (username, display) = match platform {
+    let (username, display, channel) = match platform {
         Platform::YouTube => {
-            let username = get_youtube_channel_handle(url.as_str())
+            let channel = YouTubeChannel::from_url(&url)
                 .ok_or(Error::FailExtractUsername(platform, url.clone()))?;
-            let display = format!(" YouTube(@{})", username);
-            (username.to_string(), display)
+            let display = format!(" YouTube({})", channel);
+            (channel.name().to_string(), display, Some(channel))
         }
         Platform::Twitch => {
             let username = get_twitch_username(url.as_str())
                 .ok_or(Error::FailExtractUsername(platform, url.clone()))?;
             let display = format!(" Twitch({})", username);
-            (username.to_string(), display)
+            (username.to_string(), display, None)
         }
         Platform::Unknown => return Err("Invalid url".into()),
     };
@@ -77,11 +88,18 @@ fn parse_link_item(s: &str) -> Result<LinkItem, Box<dyn std::error::Error>> {
         url,
         username,
         display,
+        channel,
     })
 }
 
 pub type Aliases = HashMap<String, Links>;
 
+/// Aliases from the `new_aliases` file, none when it's missing or invalid
+pub fn read_aliases(ctx: &Context) -> Aliases {
+    let aliases_string = fs::read_to_string(&ctx.config.new_aliases).unwrap_or("{}".to_string());
+    toml::from_str::<Aliases>(&aliases_string).unwrap_or_default()
+}
+
 pub struct PlatformFlags {
     pub youtube: bool,
     pub twitch: bool,
@@ -100,7 +118,7 @@ impl PlatformFlags {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CliTarget {
     Url(Url),
     YoutubeChannelHandle(String),
@@ -111,7 +129,7 @@ pub enum CliTarget {
 #[serde(untagged)]
 pub enum TuiTarget {
     Url(Url),
-    Links(Links),
+    Links(Box<Links>),
 }
 
 impl TuiTarget {
@@ -127,7 +145,7 @@ impl Display for TuiTarget {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let something = match self {
             TuiTarget::Url(url) => url.to_string(),
-            TuiTarget::Links(links) => links.to_string()
+            TuiTarget::Links(links) => links.to_string(),
         };
         write!(f, "{something}")
     }
@@ -161,20 +179,30 @@ impl ToCliTarget for String {
     }
 }
 
+impl Display for CliTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliTarget::Url(url) => write!(f, "{url}"),
+            CliTarget::YoutubeChannelHandle(handle) => write!(f, "@{handle}"),
+            CliTarget::MaubeAlias(alias) => write!(f, "{alias}"),
+        }
+    }
+}
+
 impl Default for CliTarget {
     fn default() -> Self {
         CliTarget::MaubeAlias("DEFAULT_NO_TARGET".to_string())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum Platform {
     YouTube,
     Twitch,
     Unknown,
 }
 
-fn detect_platform(url: &Url) -> Platform {
+pub fn detect_platform(url: &Url) -> Platform {
     match url.host_str() {
         Some(host) => {
             if host == "youtube.com" || host == "www.youtube.com" || host == "m.youtube.com" {
@@ -1,4 +1,7 @@
+use std::collections::VecDeque;
 use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
 
 use color_eyre::eyre::{OptionExt, Result};
 use crossterm::event::{
@@ -10,7 +13,7 @@ use ratatui::{
     DefaultTerminal, Frame,
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Stylize, palette::material::WHITE},
+    style::{Color, Stylize},
     text::Line,
     widgets::{Block, ListState, Widget},
 };
@@ -18,23 +21,57 @@ use url::Url;
 
 use crate::{
     context::Context,
+    error::Error,
     event::{self, Event, EventHandler, TuiEvent},
-    target::TuiTarget,
+    extractor::TimeRange,
+    formats::list_formats,
+    preset,
+    remote::{self, RemoteCommand},
+    resume,
+    runner::{StreamInfo, launch},
+    supervisor::SessionStatus,
+    target::{Platform, TuiTarget, detect_platform},
     tui::{
+        format_picker::FormatPicker,
         panel::{Panel, PanelId, PanelList},
         utils::inside_area,
     },
+    twitch::{Twitch, get_twitch_username},
+    utils::{extract_youtube_id_from_url, format_timestamp},
+    youtube::{YouTube, YouTubeChannel},
 };
 
+mod format_picker;
 mod panel;
 mod utils;
 
+pub use format_picker::FormatChoice;
+
 pub struct Tui {
     target_list: PanelList<IndexMap<String, TuiTarget>>,
     wait_list: PanelList<IndexSet<WaitItem>>,
     wait_list_priority: PanelList<IndexSet<WaitItem>>,
+    running_streams: PanelList<Vec<SessionStatus>>,
     events: EventHandler,
     focused_panel: PanelId,
+    ctx: Context,
+    /// Tell whether a dropped stream is still live, so it's reconnected
+    youtube: Arc<YouTube>,
+    /// `None` without Twitch credentials, its streams aren't reconnected then
+    twitch: Option<Arc<Twitch<'static>>>,
+    /// Applied to the next launched stream
+    range: Option<TimeRange>,
+    live_from_start: bool,
+    /// Quality preset (or raw format) of the next launched stream
+    preset: Option<String>,
+    /// Play the next launched streams without video
+    audio_only: bool,
+    /// Text typed into the range prompt while it's open
+    prompt: Option<String>,
+    format_picker: Option<FormatPicker>,
+    /// Video stopped halfway last time, waiting for whether to resume it
+    resume_offers: VecDeque<(LaunchTarget, u64)>,
+    notice: Option<String>,
     exit: bool,
     _debug_log: Vec<String>,
 }
@@ -62,11 +99,11 @@ impl Hash for WaitItem {
 }
 
 impl Tui {
-    pub fn new(ctx: &Context) -> Result<Self> {
+    pub fn new(ctx: &Context, youtube: YouTube, twitch: Option<Twitch<'static>>) -> Result<Self> {
         let focused_panel = PanelId::TargetList;
         let targets = TuiTarget::get_all(ctx)?;
         let mut target_list_state = ListState::default();
-        if targets.len() > 0 {
+        if !targets.is_empty() {
             target_list_state.select_first();
         }
         let target_list = PanelList {
@@ -96,13 +133,34 @@ impl Tui {
             area: Rect::default(),
             focused: focused_panel == PanelId::WaitListPriority,
         };
+        let running_streams = PanelList {
+            id: PanelId::RunningStreams,
+            title: String::from("Running Streams"),
+            items: Vec::new(),
+            key: '4',
+            state: ListState::default(),
+            area: Rect::default(),
+            focused: focused_panel == PanelId::RunningStreams,
+        };
         let events = EventHandler::new();
         Ok(Self {
             target_list,
             wait_list,
             wait_list_priority,
+            running_streams,
             events,
             focused_panel,
+            ctx: ctx.clone(),
+            youtube: Arc::new(youtube),
+            twitch: twitch.map(Arc::new),
+            range: ctx.range,
+            live_from_start: ctx.live_from_start,
+            preset: ctx.format.clone(),
+            audio_only: ctx.audio_only,
+            prompt: None,
+            format_picker: None,
+            resume_offers: VecDeque::new(),
+            notice: None,
             exit: false,
             _debug_log: Vec::new(),
         })
@@ -112,7 +170,7 @@ impl Tui {
         while !self.exit {
             terminal.draw(|frame| self.draw(frame))?;
             match self.events.next().await? {
-                Event::Tick => (),
+                Event::Tick => self.refresh_running_streams(),
                 Event::Crossterm(event) => match event {
                     CrosstermEvent::Key(event) => self.handle_key_event(event)?,
                     CrosstermEvent::Mouse(event) => self.handle_mouse_event(event)?,
@@ -122,14 +180,43 @@ impl Tui {
                     TuiEvent::Move(direction) => self.move_item(direction),
                     TuiEvent::MovePanel(direction) => self.move_panel(direction),
                     TuiEvent::DoAction(modifiers) => self.do_action(modifiers)?,
+                    TuiEvent::Launch => self.launch_selected()?,
+                    TuiEvent::EditRange => self.prompt = Some(String::new()),
+                    TuiEvent::CyclePreset => self.cycle_preset(),
+                    TuiEvent::ToggleAudioOnly => self.audio_only = !self.audio_only,
+                    TuiEvent::PickFormat => self.pick_format()?,
+                    TuiEvent::Formats(choices) => self.show_formats(choices),
+                    TuiEvent::Remote(command) => self.send_remote(command),
+                    TuiEvent::StopSession => self.stop_selected_session(),
+                    TuiEvent::Notify(notice) => self.notice = Some(notice),
                     TuiEvent::Exit => self.exit = true,
                 },
             }
         }
+
+        // Give the players a moment to close before the runtime goes away
+        self.ctx.supervisor.shutdown();
+        self.ctx
+            .supervisor
+            .wait_for_sessions(Duration::from_secs(5))
+            .await;
         Ok(())
     }
 
     pub fn handle_key_event(&mut self, event: KeyEvent) -> Result<()> {
+        if self.prompt.is_some() {
+            self.handle_prompt_key(event);
+            return Ok(());
+        }
+        if self.format_picker.is_some() {
+            self.handle_picker_key(event);
+            return Ok(());
+        }
+        if !self.resume_offers.is_empty() {
+            self.handle_resume_key(event);
+            return Ok(());
+        }
+
         if event.is_press() {
             match (event.modifiers, event.code) {
                 (KeyModifiers::NONE, KeyCode::Char(char)) => match char {
@@ -139,8 +226,16 @@ impl Tui {
                     'h' => self.events.send(TuiEvent::Move(event::Direction::Left)),
                     'j' => self.events.send(TuiEvent::Move(event::Direction::Down)),
                     ' ' => self.events.send(TuiEvent::DoAction(KeyModifiers::NONE)),
+                    't' => self.events.send(TuiEvent::EditRange),
+                    'f' => self.events.send(TuiEvent::CyclePreset),
+                    'a' => self.events.send(TuiEvent::ToggleAudioOnly),
+                    _ if self.focused_panel == PanelId::RunningStreams => {
+                        self.handle_remote_key(char)
+                    }
                     _ => (),
                 },
+                (KeyModifiers::NONE, KeyCode::Enter) => self.events.send(TuiEvent::Launch),
+                (KeyModifiers::SHIFT, KeyCode::Char('F')) => self.events.send(TuiEvent::PickFormat),
                 #[rustfmt::skip]
                 (KeyModifiers::CONTROL, KeyCode::Char(char)) => match char {
                     'k' => self.events.send(TuiEvent::MovePanel(event::Direction::Up)),
@@ -150,10 +245,7 @@ impl Tui {
                     ' ' => self.events.send(TuiEvent::DoAction(KeyModifiers::CONTROL)),
                     _ => (),
                 },
-                (_, KeyCode::Char(char)) => match char {
-                    ' ' => self.events.send(TuiEvent::DoAction(event.modifiers)),
-                    _ => (),
-                },
+                (_, KeyCode::Char(' ')) => self.events.send(TuiEvent::DoAction(event.modifiers)),
                 _ => (),
             }
         }
@@ -177,15 +269,105 @@ impl Tui {
         Ok(())
     }
 
-    fn handle_mouse_event(&mut self, event: MouseEvent) -> Result<()> {
-        match event.kind {
-            MouseEventKind::Down(button) if button == MouseButton::Left => {
-                let x = event.column;
-                let y = event.row;
-                self.handle_panel_click(x, y);
+    /// Player controls while the Running Streams panel is focused
+    fn handle_remote_key(&self, char: char) {
+        let command = match char {
+            'p' => RemoteCommand::Pause,
+            'm' => RemoteCommand::Mute,
+            '=' => RemoteCommand::Volume {
+                value: 5.0,
+                relative: true,
+            },
+            '-' => RemoteCommand::Volume {
+                value: -5.0,
+                relative: true,
+            },
+            ',' => RemoteCommand::Seek {
+                seconds: -10.0,
+                relative: true,
+            },
+            '.' => RemoteCommand::Seek {
+                seconds: 10.0,
+                relative: true,
+            },
+            'x' => RemoteCommand::Quit,
+            's' => return self.events.send(TuiEvent::StopSession),
+            _ => return,
+        };
+        self.events.send(TuiEvent::Remote(command));
+    }
+
+    fn handle_prompt_key(&mut self, event: KeyEvent) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        if !event.is_press() {
+            return;
+        }
+        match event.code {
+            KeyCode::Char(char) => prompt.push(char),
+            KeyCode::Backspace => {
+                prompt.pop();
+            }
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                let input = self.prompt.take().unwrap_or_default();
+                self.apply_range(input.trim());
             }
             _ => (),
         }
+    }
+
+    /// `y`/Enter resumes, `n` plays from the start and Esc skips the video,
+    /// then the next offer is up
+    fn handle_resume_key(&mut self, event: KeyEvent) {
+        if !event.is_press() {
+            return;
+        }
+        let resume = match event.code {
+            KeyCode::Char('y') | KeyCode::Enter => Some(true),
+            KeyCode::Char('n') => Some(false),
+            KeyCode::Esc => None,
+            _ => return,
+        };
+        let Some((target, position)) = self.resume_offers.pop_front() else {
+            return;
+        };
+        match resume {
+            Some(true) => self.start(
+                target,
+                Some(TimeRange {
+                    from: Some(position),
+                    to: None,
+                }),
+            ),
+            Some(false) => self.start(target, None),
+            None => (),
+        }
+    }
+
+    /// `START-END` sets the range, `live` plays live streams from their
+    /// start and an empty input clears both
+    fn apply_range(&mut self, input: &str) {
+        match input {
+            "" => {
+                self.range = None;
+                self.live_from_start = false;
+            }
+            "live" | "start" => self.live_from_start = !self.live_from_start,
+            _ => match input.parse::<TimeRange>() {
+                Ok(range) => self.range = Some(range),
+                Err(e) => self.notice = Some(e.to_string()),
+            },
+        }
+    }
+
+    fn handle_mouse_event(&mut self, event: MouseEvent) -> Result<()> {
+        if let MouseEventKind::Down(MouseButton::Left) = event.kind {
+            let x = event.column;
+            let y = event.row;
+            self.handle_panel_click(x, y);
+        }
         Ok(())
     }
 
@@ -197,10 +379,11 @@ impl Tui {
         self.handle_panel_change(panel_click_result);
     }
 
-    const PANEL_IDS: [PanelId; 3] = [
+    const PANEL_IDS: [PanelId; 4] = [
         PanelId::TargetList,
         PanelId::WaitList,
         PanelId::WaitListPriority,
+        PanelId::RunningStreams,
     ];
 
     fn handle_panel_change(&mut self, panel_click_result: Vec<Option<PanelId>>) {
@@ -224,6 +407,7 @@ impl Tui {
             PanelId::TargetList => &mut self.target_list,
             PanelId::WaitList => &mut self.wait_list,
             PanelId::WaitListPriority => &mut self.wait_list_priority,
+            PanelId::RunningStreams => &mut self.running_streams,
         }
     }
 
@@ -267,25 +451,22 @@ impl Tui {
                         }
                     }
 
-                    if self.wait_list.items.len() > 0 {
+                    if !self.wait_list.items.is_empty() {
                         self.wait_list.state.select_first();
                     }
                 }
             }
             PanelId::WaitList => {
-                if let Some(selected) = self.wait_list.state.selected() {
-                    match modifiers {
-                        KeyModifiers::CONTROL => {
-                            self.wait_list_priority.items.insert(
-                                self.wait_list
-                                    .items
-                                    .get_index(selected)
-                                    .cloned()
-                                    .ok_or_eyre("Failed to get selected wait_item")?,
-                            );
-                        }
-                        _ => (),
-                    }
+                if let Some(selected) = self.wait_list.state.selected()
+                    && modifiers == KeyModifiers::CONTROL
+                {
+                    self.wait_list_priority.items.insert(
+                        self.wait_list
+                            .items
+                            .get_index(selected)
+                            .cloned()
+                            .ok_or_eyre("Failed to get selected wait_item")?,
+                    );
                 }
             }
             _ => (),
@@ -294,6 +475,253 @@ impl Tui {
         Ok(())
     }
 
+    fn launch_selected(&mut self) -> Result<()> {
+        for mut target in self.selected_targets()? {
+            target.format = self.preset.clone().or(target.format);
+            target.audio_only |= self.audio_only;
+            self.launch(target);
+        }
+        Ok(())
+    }
+
+    /// Every stream behind the selected item, with the alias settings
+    fn selected_targets(&self) -> Result<Vec<LaunchTarget>> {
+        let targets = match self.focused_panel {
+            PanelId::TargetList => {
+                let Some(index) = self.target_list.state.selected() else {
+                    return Ok(vec![]);
+                };
+                let (name, target) = self
+                    .target_list
+                    .items
+                    .get_index(index)
+                    .ok_or_eyre(format!("Target with index {index} not found"))?;
+                match target {
+                    TuiTarget::Url(url) => vec![LaunchTarget::new(name, url)],
+                    TuiTarget::Links(links) => [&links.youtube, &links.twitch]
+                        .into_iter()
+                        .flatten()
+                        .map(|link| LaunchTarget {
+                            format: links.format.clone(),
+                            audio_only: links.audio_only,
+                            ..LaunchTarget::new(name, &link.url)
+                        })
+                        .collect(),
+                }
+            }
+            PanelId::WaitList | PanelId::WaitListPriority => {
+                let list = match self.focused_panel {
+                    PanelId::WaitList => &self.wait_list,
+                    _ => &self.wait_list_priority,
+                };
+                list.state
+                    .selected()
+                    .and_then(|index| list.items.get_index(index))
+                    .map(|item| vec![LaunchTarget::new(&item.name, &item.url)])
+                    .unwrap_or_default()
+            }
+            PanelId::RunningStreams => vec![],
+        };
+        Ok(targets)
+    }
+
+    /// List the formats of the selected item's streams in a popup
+    fn pick_format(&mut self) -> Result<()> {
+        let targets = self.selected_targets()?;
+        if targets.is_empty() {
+            return Ok(());
+        }
+        self.notice = Some(String::from("Listing formats..."));
+
+        let config = self.ctx.config.clone();
+        let sender = self.events.sender();
+        tokio::spawn(async move {
+            let mut choices = Vec::new();
+            for target in targets {
+                let (platform, _, stream_url) = stream_url(&target.url);
+                let extractor = config.extractor(&platform);
+                match list_formats(extractor.as_ref(), stream_url.as_str()).await {
+                    Ok(formats) => choices.extend(formats.into_iter().map(|format| FormatChoice {
+                        target: target.clone(),
+                        platform,
+                        format,
+                    })),
+                    Err(e) => {
+                        let notice = format!("{} ({platform}): {e}", target.name);
+                        let _ = sender.send(Event::Tui(TuiEvent::Notify(notice)));
+                        return;
+                    }
+                }
+            }
+            let _ = sender.send(Event::Tui(TuiEvent::Formats(choices)));
+        });
+        Ok(())
+    }
+
+    fn show_formats(&mut self, choices: Vec<FormatChoice>) {
+        if choices.is_empty() {
+            self.notice = Some(String::from("No formats found"));
+            return;
+        }
+        self.notice = None;
+        self.format_picker = Some(FormatPicker::new(choices));
+    }
+
+    fn handle_picker_key(&mut self, event: KeyEvent) {
+        let Some(picker) = self.format_picker.as_mut() else {
+            return;
+        };
+        if !event.is_press() {
+            return;
+        }
+        match event.code {
+            KeyCode::Char('j') | KeyCode::Down => picker.state.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => picker.state.select_previous(),
+            KeyCode::Esc | KeyCode::Char('q') => self.format_picker = None,
+            KeyCode::Enter => {
+                if let Some(choice) = picker.selected().cloned() {
+                    self.format_picker = None;
+                    self.launch(LaunchTarget {
+                        format: Some(choice.format.id),
+                        audio_only: choice.target.audio_only || self.audio_only,
+                        ..choice.target
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Launch the target, or first offer to resume a video stopped halfway
+    fn launch(&mut self, target: LaunchTarget) {
+        let (_, video_id, _) = stream_url(&target.url);
+        if self.range.is_none()
+            && let Some(video_id) = video_id
+            && let Some(position) = resume::position(&self.ctx.config.resume, &video_id)
+        {
+            self.resume_offers.push_back((target, position));
+            return;
+        }
+        self.start(target, self.range);
+    }
+
+    fn start(&mut self, target: LaunchTarget, range: Option<TimeRange>) {
+        let (platform, video_id, url) = stream_url(&target.url);
+
+        let mut ctx = self.ctx.clone();
+        ctx.range = range;
+        ctx.live_from_start = self.live_from_start;
+        ctx.format = target.format;
+        ctx.audio_only = target.audio_only;
+        let info = StreamInfo {
+            alias: target.name,
+            platform,
+            video_id,
+            title: None,
+            start_time: None,
+            scheduled_start: None,
+        };
+        self.notice = Some(format!("Launched {info}"));
+
+        let channel = YouTubeChannel::from_url(&target.url);
+        let username = get_twitch_username(target.url.as_str()).map(String::from);
+        let (youtube, twitch) = (self.youtube.clone(), self.twitch.clone());
+        let sender = self.events.sender();
+        tokio::spawn(async move {
+            // Only positions in finished videos are remembered, so one that
+            // can't be looked up is treated as a broadcast
+            if let Some(video_id) = &info.video_id {
+                ctx.vod = youtube.is_finished(video_id).await.unwrap_or(false);
+            }
+            let still_live = || async {
+                Ok(match (info.platform, &info.video_id) {
+                    (Platform::YouTube, Some(video_id)) => youtube.is_still_live(video_id).await?,
+                    (Platform::YouTube, None) => match &channel {
+                        Some(channel) => youtube.live_url(channel, &ctx).await?.is_some(),
+                        None => false,
+                    },
+                    (Platform::Twitch, _) => match (&twitch, &username) {
+                        (Some(twitch), Some(username)) => twitch.is_live(username).await?,
+                        _ => false,
+                    },
+                    (Platform::Unknown, _) => false,
+                })
+            };
+            let result = launch(&ctx, url.to_string(), &info, still_live).await;
+            if let Err(e) = result {
+                let notice = match e.downcast_ref::<Error>().and_then(Error::hint) {
+                    Some(hint) => format!("{info}: {e}. {hint}"),
+                    None => format!("{info}: {e}"),
+                };
+                let _ = sender.send(Event::Tui(TuiEvent::Notify(notice)));
+            }
+        });
+    }
+
+    /// Step through the presets, after the last one back to the default format
+    fn cycle_preset(&mut self) {
+        let names = preset::names(&self.ctx.config.presets);
+        let next = match &self.preset {
+            Some(current) => names
+                .iter()
+                .position(|name| name == current)
+                .and_then(|index| names.get(index + 1)),
+            None => names.first(),
+        };
+        self.preset = next.cloned();
+    }
+
+    fn refresh_running_streams(&mut self) {
+        self.running_streams.items = self.ctx.supervisor.sessions();
+        let count = self.running_streams.items.len();
+        match self.running_streams.state.selected() {
+            _ if count == 0 => self.running_streams.state.select(None),
+            Some(selected) if selected >= count => {
+                self.running_streams.state.select(Some(count - 1))
+            }
+            None => self.running_streams.state.select_first(),
+            _ => (),
+        }
+    }
+
+    fn selected_session(&self) -> Option<&SessionStatus> {
+        self.running_streams
+            .state
+            .selected()
+            .and_then(|index| self.running_streams.items.get(index))
+    }
+
+    fn send_remote(&mut self, command: RemoteCommand) {
+        let Some(session) = self.selected_session() else {
+            return;
+        };
+        let Some(socket) = session.ipc_socket.clone() else {
+            // Players without remote control can still be stopped
+            if command == RemoteCommand::Quit {
+                self.stop_selected_session();
+            } else {
+                self.notice = Some(format!("{} can't be controlled", session.label));
+            }
+            return;
+        };
+
+        let label = session.label.clone();
+        let sender = self.events.sender();
+        tokio::spawn(async move {
+            if let Err(e) = remote::send(&socket, &command).await {
+                let _ = sender.send(Event::Tui(TuiEvent::Notify(format!("{label}: {e}"))));
+            }
+        });
+    }
+
+    fn stop_selected_session(&mut self) {
+        if let Some(session) = self.selected_session() {
+            let (id, label) = (session.id, session.label.clone());
+            self.ctx.supervisor.stop(id);
+            self.notice = Some(format!("Stopped {label}"));
+        }
+    }
+
     pub fn move_item(&mut self, direction: event::Direction) {
         match self.focused_panel {
             PanelId::TargetList => match direction {
@@ -311,6 +739,11 @@ impl Tui {
                 event::Direction::Down => self.wait_list_priority.state.select_next(),
                 _ => (),
             },
+            PanelId::RunningStreams => match direction {
+                event::Direction::Up => self.running_streams.state.select_previous(),
+                event::Direction::Down => self.running_streams.state.select_next(),
+                _ => (),
+            },
         }
     }
 
@@ -323,6 +756,12 @@ impl Tui {
             PanelId::WaitListPriority => match direction {
                 event::Direction::Up => PanelId::TargetList,
                 event::Direction::Left => PanelId::WaitList,
+                event::Direction::Right => PanelId::RunningStreams,
+                _ => self.focused_panel,
+            },
+            PanelId::RunningStreams => match direction {
+                event::Direction::Up => PanelId::TargetList,
+                event::Direction::Left => PanelId::WaitListPriority,
                 _ => self.focused_panel,
             },
             PanelId::WaitList => match direction {
@@ -353,6 +792,40 @@ impl Tui {
     }
 }
 
+/// A stream about to be launched from the TUI
+#[derive(Debug, Clone)]
+pub struct LaunchTarget {
+    pub name: String,
+    pub url: Url,
+    /// Format or preset, the default format when `None`
+    pub format: Option<String>,
+    pub audio_only: bool,
+}
+
+impl LaunchTarget {
+    fn new(name: &str, url: &Url) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.clone(),
+            format: None,
+            audio_only: false,
+        }
+    }
+}
+
+/// Channel links are played through their live page, so no API requests are
+/// needed
+fn stream_url(url: &Url) -> (Platform, Option<String>, Url) {
+    let platform = detect_platform(url);
+    let video_id = extract_youtube_id_from_url(url);
+    let live_page = video_id
+        .is_none()
+        .then(|| YouTubeChannel::from_url(url))
+        .flatten()
+        .and_then(|channel| Url::parse(&format!("{}/live", channel.url())).ok());
+    (platform, video_id, live_page.unwrap_or_else(|| url.clone()))
+}
+
 #[derive(Debug, Clone)]
 pub enum ColorType {
     Primary,
@@ -373,18 +846,52 @@ impl Widget for &mut Tui {
 
         let inside_area = inside_area(&layout[0]);
 
-        Line::from(vec![
-            "Targets(".into(),
-            self.target_list.items.len().to_string().into(),
-            ") | ".into(),
-            "WaitList(".into(),
-            self.wait_list.items.len().to_string().into(),
-            ") | ".into(),
-            "Priority(".into(),
-            self.wait_list_priority.items.len().to_string().into(),
-            ")".into(),
-        ])
-        .render(inside_area, buf);
+        let info_line = match (&self.prompt, self.resume_offers.front()) {
+            (Some(prompt), _) => Line::from(vec![
+                "Range (1:23:45-1:40:00, 10:00-, live, empty clears): ".into(),
+                prompt.clone().fg(Color::Cyan),
+            ]),
+            (None, Some((target, position))) => Line::from(vec![
+                format!("{} was stopped at ", target.name).into(),
+                format_timestamp(*position).fg(Color::Cyan),
+                ", resume from there? (y/n, Esc skips)".into(),
+                match self.resume_offers.len() {
+                    1 => String::new(),
+                    offers => format!(" {} more to go", offers - 1),
+                }
+                .into(),
+            ]),
+            (None, None) => {
+                let mut spans = vec![
+                    "Targets(".into(),
+                    self.target_list.items.len().to_string().into(),
+                    ") | ".into(),
+                    "WaitList(".into(),
+                    self.wait_list.items.len().to_string().into(),
+                    ") | ".into(),
+                    "Priority(".into(),
+                    self.wait_list_priority.items.len().to_string().into(),
+                    ")".into(),
+                ];
+                if let Some(range) = self.range {
+                    spans.push(format!(" | Range({range})").into());
+                }
+                if self.live_from_start {
+                    spans.push(" | From start".into());
+                }
+                if let Some(preset) = &self.preset {
+                    spans.push(format!(" | Preset({preset})").into());
+                }
+                if self.audio_only {
+                    spans.push(" | Audio only".into());
+                }
+                if let Some(notice) = &self.notice {
+                    spans.push(format!(" | {notice}").fg(Color::Cyan));
+                }
+                Line::from(spans)
+            }
+        };
+        info_line.render(inside_area, buf);
 
         Block::bordered()
             .title(" [0] Info ")
@@ -434,9 +941,19 @@ impl Widget for &mut Tui {
             buf,
         );
 
-        Block::bordered()
-            .title(" [4] Running Streams ")
-            .fg(WHITE)
-            .render(bottom_layout[2], buf);
+        self.running_streams.draw(
+            |session| {
+                Vec::from([
+                    (session.label.clone(), ColorType::Primary),
+                    (format!(" {}", session.throughput), ColorType::Secondary),
+                ])
+            },
+            bottom_layout[2],
+            buf,
+        );
+
+        if let Some(picker) = self.format_picker.as_mut() {
+            picker.draw(area, buf);
+        }
     }
 }
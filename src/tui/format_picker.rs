@@ -0,0 +1,93 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Padding, StatefulWidget, Widget},
+};
+
+use crate::extractor::FormatInfo;
+use crate::target::Platform;
+use crate::tui::{
+    ColorType, LaunchTarget,
+    utils::{border_color, line_style, text_color},
+};
+
+/// A format offered by one of the selected target's streams
+#[derive(Debug, Clone)]
+pub struct FormatChoice {
+    /// Launched the same way as without picking a format
+    pub target: LaunchTarget,
+    pub platform: Platform,
+    pub format: FormatInfo,
+}
+
+/// Popup listing the formats of the selected target
+pub struct FormatPicker {
+    pub choices: Vec<FormatChoice>,
+    pub state: ListState,
+}
+
+impl FormatPicker {
+    pub fn new(choices: Vec<FormatChoice>) -> Self {
+        let mut state = ListState::default();
+        if !choices.is_empty() {
+            state.select_first();
+        }
+        Self { choices, state }
+    }
+
+    pub fn selected(&self) -> Option<&FormatChoice> {
+        self.state
+            .selected()
+            .and_then(|index| self.choices.get(index))
+    }
+
+    pub fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        let [area] = Layout::vertical([Constraint::Percentage(70)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(70)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let width = self
+            .choices
+            .iter()
+            .map(|choice| choice.format.id.len())
+            .max()
+            .unwrap_or_default();
+        let selected = self.state.selected();
+        let items = self
+            .choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| {
+                let selected = selected == Some(i);
+                Line::from(vec![
+                    Span::from(format!("{:width$}  ", choice.format.id)).fg(text_color(
+                        true,
+                        &ColorType::Primary,
+                        selected,
+                    )),
+                    Span::from(format!(
+                        "{} ({}) {}",
+                        choice.target.name, choice.platform, choice.format.description
+                    ))
+                    .fg(text_color(true, &ColorType::Secondary, selected)),
+                ])
+                .style(line_style(true, selected))
+            })
+            .collect::<Vec<Line>>();
+
+        let list = List::new(items).block(
+            Block::bordered()
+                .title(" Formats ")
+                .title_bottom(" Enter launches, Esc closes ")
+                .padding(Padding::horizontal(1))
+                .fg(border_color(true)),
+        );
+        Clear.render(area, buf);
+        StatefulWidget::render(list, area, buf, &mut self.state);
+    }
+}
@@ -19,6 +19,7 @@ pub enum PanelId {
     TargetList,
     WaitListPriority,
     WaitList,
+    RunningStreams,
 }
 
 pub struct PanelList<Items> {
@@ -56,13 +57,11 @@ where
     }
 
     fn handle_key_event(&mut self, event: KeyEvent) -> Option<PanelId> {
-        if event.is_press() {
-            if event.code == KeyCode::Char(self.key) {
-                self.focused = true;
-                return Some(self.id);
-            }
+        if event.is_press() && event.code == KeyCode::Char(self.key) {
+            self.focused = true;
+            return Some(self.id);
         }
-        return None;
+        None
     }
 
     fn handle_click(&mut self, x: u16, y: u16) -> Option<PanelId> {
@@ -81,9 +80,9 @@ where
             }
 
             self.focused = true;
-            return Some(self.id);
+            Some(self.id)
         } else {
-            return None;
+            None
         }
     }
 }
@@ -92,7 +91,7 @@ impl<Items> PanelList<Items>
 where
     for<'t> &'t Items: IntoIterator,
 {
-    pub fn draw<'a, F>(&mut self, list_fn: F, area: Rect, buf: &mut Buffer)
+    pub fn draw<F>(&mut self, list_fn: F, area: Rect, buf: &mut Buffer)
     where
         F: FnMut(<&Items as IntoIterator>::Item) -> Vec<(String, ColorType)>,
     {
@@ -112,7 +111,7 @@ where
                 )
                 .style(line_style(self.focused, selected))
             })
-            .collect::<Vec<Line<'a>>>();
+            .collect::<Vec<Line>>();
 
         let list = List::new(items).block(
             Block::bordered()
@@ -132,6 +131,7 @@ impl Display for PanelId {
             PanelId::TargetList => write!(f, "TargetList"),
             PanelId::WaitList => write!(f, "WaitList"),
             PanelId::WaitListPriority => write!(f, "WaitListPriority"),
+            PanelId::RunningStreams => write!(f, "RunningStreams"),
         }
     }
 }
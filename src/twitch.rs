@@ -2,16 +2,18 @@ use std::fs;
 
 use twitch_api::{
     TwitchClient,
-    helix::streams::{GetStreamsRequest, Stream},
+    helix::streams::{GetStreamsRequest, Stream, StreamType},
     twitch_oauth2::{AccessToken, AppAccessToken, ClientSecret},
-    types::{Collection, UserName},
+    types::{Collection, UserId},
 };
 
+use crate::cache::{self, CacheConfig, Kind};
 use crate::config::Config;
 
 pub struct Twitch<'a> {
     pub client: TwitchClient<'a, reqwest::Client>,
     pub token: AppAccessToken,
+    cache: CacheConfig,
 }
 
 impl<'a> Twitch<'a> {
@@ -31,13 +33,38 @@ impl<'a> Twitch<'a> {
         .await
         .inspect_err(|e| eprintln!("The frigg {e}"))?;
 
-        Ok(Self { client, token })
+        Ok(Self {
+            client,
+            token,
+            cache: config.cache.clone(),
+        })
+    }
+
+    /// Id of the account, which stays the same when it's renamed
+    async fn get_user_id(&self, username: &str) -> color_eyre::Result<Option<String>> {
+        let login = username.to_lowercase();
+        if let Some(user_id) = cache::get(&self.cache, Kind::TwitchUsers, &login) {
+            return Ok(Some(user_id));
+        }
+
+        let user_id = self
+            .client
+            .helix
+            .get_user_from_login(login.as_str(), &self.token)
+            .await?
+            .map(|user| user.id.take());
+        if let Some(user_id) = &user_id {
+            cache::insert(&self.cache, Kind::TwitchUsers, [(login, user_id)]);
+        }
+        Ok(user_id)
     }
 
     pub async fn get_streams(&self, username: &str) -> color_eyre::Result<Vec<Stream>> {
-        let usernames = Collection::from(vec![UserName::new(username.to_string())]);
+        let Some(user_id) = self.get_user_id(username).await? else {
+            return Ok(Vec::new());
+        };
 
-        let req = GetStreamsRequest::user_logins(usernames);
+        let req = GetStreamsRequest::user_ids(Collection::from(vec![UserId::new(user_id)]));
 
         let response = self
             .client
@@ -46,7 +73,14 @@ impl<'a> Twitch<'a> {
             .await
             .inspect_err(|e| println!("The frig {e}"))?;
 
-        return Ok(response.data);
+        Ok(response.data)
+    }
+
+    pub async fn is_live(&self, username: &str) -> color_eyre::Result<bool> {
+        let streams = self.get_streams(username).await?;
+        Ok(streams
+            .iter()
+            .any(|stream| stream.type_ == StreamType::Live))
     }
 }
 
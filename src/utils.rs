@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use url::Url;
 
 // pub fn get_webpage_url(url: &str) -> Option<Url> {
@@ -40,23 +42,66 @@ use url::Url;
 //     }
 // }
 
-// pub fn get_list_formats(url: &Url) {
-//     let output = Command::new("yt-dlp")
-//         .arg(url.as_str())
-//         .arg("--print")
-//         .arg("formats_table")
-//         .arg("--cookies-from-browser")
-//         .arg("firefox")
-//         .output()
-//         .unwrap();
-//     if output.status.success() {
-//         let formats_table = String::from_utf8(output.stdout).unwrap();
-//         println!("{}", formats_table);
-//     } else {
-//         eprintln!("Failed to get formats table");
-//         eprintln!("{}", String::from_utf8(output.stderr).unwrap());
-//     }
-// }
+/// Parse `1:23:45`, `23:45`, `45` or `1h23m45s` into seconds
+pub fn parse_timestamp(input: &str) -> Option<u64> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    if input.ends_with(['h', 'm', 's']) {
+        let mut seconds = 0;
+        let mut number = String::new();
+        for c in input.chars() {
+            match c {
+                '0'..='9' => number.push(c),
+                'h' | 'm' | 's' => {
+                    let value = number.parse::<u64>().ok()?;
+                    number.clear();
+                    seconds += value
+                        * match c {
+                            'h' => 3600,
+                            'm' => 60,
+                            _ => 1,
+                        };
+                }
+                _ => return None,
+            }
+        }
+        return Some(seconds);
+    }
+
+    let parts = input.split(':').collect::<Vec<_>>();
+    if parts.len() > 3 {
+        return None;
+    }
+    parts.iter().try_fold(0, |seconds, part| {
+        let value = part.parse::<u64>().ok()?;
+        Some(seconds * 60 + value)
+    })
+}
+
+/// Where streamdex keeps what it remembers between runs
+pub fn data_dir() -> PathBuf {
+    match std::env::var_os("XDG_DATA_HOME") {
+        Some(data_home) => PathBuf::from(data_home).join("streamdex"),
+        None => {
+            let home = std::env::var("HOME")
+                .or_else(|_| std::env::var("USERPROFILE"))
+                .unwrap_or_default();
+            PathBuf::from(home).join(".local/share/streamdex")
+        }
+    }
+}
+
+/// Format seconds as `1:23:45`, or `23:45` under an hour
+pub fn format_timestamp(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match hours {
+        0 => format!("{minutes}:{seconds:02}"),
+        _ => format!("{hours}:{minutes:02}:{seconds:02}"),
+    }
+}
 
 pub fn _extract_youtube_id(input: &str) -> Option<String> {
     // Normalize the input - add protocol if missing
@@ -92,14 +137,12 @@ pub fn extract_youtube_id_from_url(url: &Url) -> Option<String> {
     }
 
     // Handle youtu.be short URLs
-    if host.contains("youtu.be") {
-        if let Some(mut segments) = url.path_segments() {
-            if let Some(id) = segments.next() {
-                if is_valid_video_id(id) {
-                    return Some(id.to_string());
-                }
-            }
-        }
+    if host.contains("youtu.be")
+        && let Some(mut segments) = url.path_segments()
+        && let Some(id) = segments.next()
+        && is_valid_video_id(id)
+    {
+        return Some(id.to_string());
     }
 
     // Handle path-based URLs
@@ -116,16 +159,16 @@ pub fn extract_youtube_id_from_url(url: &Url) -> Option<String> {
     }
 
     // Handle user pages with fragment like #p/u/1/VIDEO_ID or #p/a/u/2/VIDEO_ID
-    if let Some(fragment) = url.fragment() {
-        if fragment.starts_with("p/") {
-            // Split by / and get the last segment, then clean it from query params
-            let parts: Vec<&str> = fragment.split('/').collect();
-            if let Some(last) = parts.last() {
-                // Remove any query parameters that might be in the fragment
-                let clean_id = last.split('?').next().unwrap_or(last);
-                if is_valid_video_id(clean_id) {
-                    return Some(clean_id.to_string());
-                }
+    if let Some(fragment) = url.fragment()
+        && fragment.starts_with("p/")
+    {
+        // Split by / and get the last segment, then clean it from query params
+        let parts: Vec<&str> = fragment.split('/').collect();
+        if let Some(last) = parts.last() {
+            // Remove any query parameters that might be in the fragment
+            let clean_id = last.split('?').next().unwrap_or(last);
+            if is_valid_video_id(clean_id) {
+                return Some(clean_id.to_string());
             }
         }
     }
@@ -212,11 +255,33 @@ mod tests {
     fn test_parsed_url_struct() {
         let url = Url::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
         assert_eq!(
-            _extract_youtube_id(&url.as_str()),
+            _extract_youtube_id(url.as_str()),
             Some("dQw4w9WgXcQ".to_string())
         );
     }
 
+    #[test]
+    fn test_timestamps() {
+        #[rustfmt::skip]
+        let test_cases = vec![
+            ("1:23:45", Some(5025)),
+            ("23:45", Some(1425)),
+            ("45", Some(45)),
+            ("01:02:03", Some(3723)),
+            ("1h23m45s", Some(5025)),
+            ("90m", Some(5400)),
+            ("1:2:3:4", None),
+            ("1:xx", None),
+            ("", None),
+        ];
+
+        for (input, expected) in test_cases {
+            assert_eq!(parse_timestamp(input), expected, "Failed to parse: {input}");
+        }
+        assert_eq!(format_timestamp(5025), "1:23:45");
+        assert_eq!(format_timestamp(65), "1:05");
+    }
+
     #[test]
     fn test_invalid_url() {
         let url = "https://www.example.com/watch?v=dQw4w9WgXcQ";